@@ -0,0 +1,234 @@
+//! Implement the `Block` and `Color` traits for the custom cell representation
+//! and use it through the whole pipeline: parse the puzzle, solve it and render the solution.
+//!
+//! The `ThreeColor` fits the puzzles with only the black and the red blocks
+//! into a single byte instead of the `u32` used by the generic multicolor cells.
+//!
+//! ```
+//! cargo run --example custom_color
+//! ```
+
+use std::{
+    fmt,
+    ops::{Add, Sub},
+};
+
+use nonogrid::{
+    parser::MyFormat,
+    render::{Renderer, ShellRenderer},
+    Block, Board, BoardParser, Color, ColorId, DynamicColor, FullProbe, LineSolver,
+};
+
+/// The puzzle with the default (black) and the red blocks.
+/// The parser assigns the IDs as the powers of two in the order of the colors definition:
+/// 1 for the white, 2 for the black and 4 for the red.
+const PUZZLE: &str = r#"
+[clues]
+rows = "2r 2r, 7r, 5r, 3r, 1, 5, 1"
+columns = "1r, 3r 1, 4r 1, 3r 3, 4r 1, 3r 1, 1r"
+
+[colors]
+defs = ["r = (red) %"]
+"#;
+
+/// The set of colors the cell can be, one bit per color.
+/// The bits are the same as the color IDs given by the parser,
+/// so the conversion from and into the `ColorId` is trivial.
+#[derive(Debug, PartialEq, Eq, Hash, Default, Copy, Clone)]
+struct ThreeColor(u8);
+
+impl ThreeColor {
+    const WHITE: Self = Self(1);
+    const BLACK: Self = Self(2);
+    const RED: Self = Self(4);
+
+    fn from_id(id: ColorId) -> Self {
+        assert!(id < 8, "Only three colors are supported");
+        #[allow(clippy::cast_possible_truncation)]
+        Self(id as u8)
+    }
+}
+
+impl Color for ThreeColor {
+    fn blank() -> Self {
+        Self::WHITE
+    }
+
+    fn is_solved(self) -> bool {
+        self.0.is_power_of_two()
+    }
+
+    fn solution_rate(self, all_colors: &[ColorId]) -> f64 {
+        let all_colors = Self::from_color_ids(all_colors);
+        let full_size = all_colors.0.count_ones();
+        let current_size = (self.0 & all_colors.0).count_ones();
+        if current_size <= 1 || full_size <= 1 {
+            return f64::from(current_size);
+        }
+
+        f64::from(full_size - current_size) / f64::from(full_size - 1)
+    }
+
+    fn variants(self) -> Vec<Self> {
+        [Self::WHITE, Self::BLACK, Self::RED]
+            .iter()
+            .copied()
+            .filter(|color| self.can_be(*color))
+            .collect()
+    }
+
+    fn as_color_id(self) -> Option<ColorId> {
+        Some(ColorId::from(self.0))
+    }
+
+    fn from_color_ids(ids: &[ColorId]) -> Self {
+        ids.iter()
+            .map(|&id| Self::from_id(id))
+            .fold(Self::default(), Self::add_color)
+    }
+}
+
+/// Required by the line solver to collect the possible colors of every cell.
+impl DynamicColor for ThreeColor {
+    fn both_colors() -> Option<Self> {
+        None
+    }
+
+    fn can_be_blank(&self) -> bool {
+        self.can_be(Self::WHITE)
+    }
+
+    fn can_be(self, color: Self) -> bool {
+        (self.0 & color.0) != 0
+    }
+
+    fn add_color(self, color: Self) -> Self {
+        Self(self.0 | color.0)
+    }
+
+    fn solved_copy(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// Setting the cell to a new state simply replaces the old one.
+impl Add for ThreeColor {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        rhs
+    }
+}
+
+/// Exclude the colors from the unsolved cell.
+impl Sub for ThreeColor {
+    type Output = Result<Self, String>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        if self.is_solved() {
+            return Err(format!("Cannot unset already set cell {:?}", self));
+        }
+
+        let remaining = self.0 & !rhs.0;
+        if remaining == 0 {
+            return Err(format!("Cannot unset the colors {:?} from {:?}", rhs, self));
+        }
+        Ok(Self(remaining))
+    }
+}
+
+/// Used by the renderer for the cells which colors are not found in the palette.
+impl fmt::Display for ThreeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match *self {
+            Self::WHITE => '.',
+            Self::BLACK => 'X',
+            Self::RED => '%',
+            _unsolved => '?',
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Default, Copy, Clone)]
+struct ThreeColorBlock {
+    size: usize,
+    color: ThreeColor,
+}
+
+impl Block for ThreeColorBlock {
+    type Color = ThreeColor;
+
+    fn from_size_and_color(size: usize, color: Option<ColorId>) -> Self {
+        if size == 0 {
+            return Self::default();
+        }
+
+        let color = color.map_or(ThreeColor::BLACK, ThreeColor::from_id);
+        Self { size, color }
+    }
+
+    /// The same colored blocks require a space between them,
+    /// but the different colored ones can go next to each other.
+    fn partial_sums(desc: &[Self]) -> Vec<usize> {
+        let mut sums = Vec::with_capacity(desc.len());
+        let mut prev: Option<(usize, ThreeColor)> = None;
+        for block in desc {
+            let current = prev.map_or(0, |(sum, color)| {
+                let gap_size = if color == block.color { 1 } else { 0 };
+                sum + gap_size
+            }) + block.size;
+
+            sums.push(current);
+            prev = Some((current, block.color));
+        }
+        sums
+    }
+
+    fn size(self) -> usize {
+        self.size
+    }
+
+    fn color(self) -> Self::Color {
+        self.color
+    }
+}
+
+/// Used by the renderer to show the clues.
+impl fmt::Display for ThreeColorBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.size)
+    }
+}
+
+fn main() {
+    let parser = MyFormat::with_content(PUZZLE).expect("Cannot parse the puzzle");
+    let board = parser.parse_rc::<ThreeColorBlock>();
+
+    #[cfg(not(feature = "sat"))]
+    {
+        let backtracking =
+            nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(1), None, None)
+                .expect("The puzzle should be solvable");
+        if let Some(solution) =
+            backtracking.and_then(|backtracking| backtracking.solutions.into_iter().next())
+        {
+            Board::restore_with_callback(&board, solution);
+        }
+    }
+    #[cfg(feature = "sat")]
+    {
+        let solutions = nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(1))
+            .expect("The puzzle should be solvable");
+        if let Some(solution) = solutions.and_then(|mut solutions| solutions.next()) {
+            Board::restore_with_callback(&board, solution);
+        }
+    }
+    assert!(
+        board.read().is_solved_full(),
+        "The puzzle has several solutions"
+    );
+
+    let renderer = ShellRenderer::with_board(board);
+    println!("{}", renderer.render());
+}
@@ -0,0 +1,148 @@
+//! Generate a random black-and-white puzzle with the unique solution,
+//! show its clues and then solve it back.
+//!
+//! ```
+//! cargo run --example generate
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nonogrid::{
+    clues_from_solution,
+    render::{Renderer, ShellRenderer},
+    BinaryBlock, BinaryColor, Board, FullProbe, LineSolver, RcBoard,
+};
+
+const SIZE: usize = 15;
+const MAX_ATTEMPTS: usize = 1000;
+
+/// The simplest pseudo-random generator (xorshift)
+/// to not bring any dependencies for the example.
+struct XorShift(u64);
+
+impl XorShift {
+    fn from_time() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        // the state should never be zero
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Returns `true` with the probability of `percent`%.
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+}
+
+const WHITE: u32 = 0;
+const BLACK: u32 = 1;
+
+fn random_solution(rng: &mut XorShift) -> Vec<Vec<u32>> {
+    (0..SIZE)
+        .map(|_| {
+            (0..SIZE)
+                .map(|_| if rng.chance(60) { BLACK } else { WHITE })
+                .collect()
+        })
+        .collect()
+}
+
+fn make_board(solution: &[Vec<u32>]) -> RcBoard<BinaryBlock> {
+    let (columns, rows) = clues_from_solution(solution, WHITE);
+    RcBoard::new(Board::with_descriptions_and_palette(rows, columns, None))
+}
+
+/// Solve the board and return whether the solution is unique.
+#[cfg(not(feature = "sat"))]
+fn solve_unique(board: &RcBoard<BinaryBlock>) -> bool {
+    let backtracking =
+        nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(2), None, None)
+            .expect("Generated puzzle should be solvable");
+
+    match backtracking {
+        // solved without any guessing
+        None => true,
+        Some(backtracking) => {
+            let solutions = backtracking.solutions;
+            if board.read().is_solved_full() {
+                return solutions.len() <= 1;
+            }
+
+            if solutions.len() == 1 {
+                let solution = solutions.into_iter().next().expect("Single solution");
+                Board::restore_with_callback(board, solution);
+                return true;
+            }
+            false
+        }
+    }
+}
+
+/// Solve the board and return whether the solution is unique.
+#[cfg(feature = "sat")]
+fn solve_unique(board: &RcBoard<BinaryBlock>) -> bool {
+    let solutions = nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(2))
+        .expect("Generated puzzle should be solvable");
+
+    match solutions {
+        None => true,
+        Some(solutions) => {
+            let solutions: Vec<_> = solutions.collect();
+            if solutions.len() == 1 {
+                let solution = solutions.into_iter().next().expect("Single solution");
+                Board::restore_with_callback(board, solution);
+                return true;
+            }
+            false
+        }
+    }
+}
+
+fn main() {
+    let mut rng = XorShift::from_time();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let solution = random_solution(&mut rng);
+        let board = make_board(&solution);
+
+        if !solve_unique(&board) {
+            continue;
+        }
+
+        println!("Generated the unique puzzle on attempt #{}:", attempt);
+        let renderer = ShellRenderer::with_board(board.clone());
+        println!("{}", renderer.render());
+
+        let board = board.read();
+        for (row, expected) in board.iter_rows().zip(&solution) {
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|&cell| {
+                    if cell == BLACK {
+                        BinaryColor::Black
+                    } else {
+                        BinaryColor::White
+                    }
+                })
+                .collect();
+            assert_eq!(row, expected.as_slice());
+        }
+        println!("The solution matches the generated image");
+        return;
+    }
+
+    panic!(
+        "Cannot generate a unique puzzle in {} attempts",
+        MAX_ATTEMPTS
+    );
+}
@@ -0,0 +1,58 @@
+//! Make a puzzle from the PNG image, solve it
+//! and draw the solution back into another PNG.
+//!
+//! ```
+//! cargo run --features=png --example solve_image [examples/rc.png]
+//! ```
+
+use std::env;
+
+use nonogrid::{
+    parser::ImageParser, render::PngRenderer, BinaryBlock, Board, BoardParser, FullProbe,
+    LineSolver,
+};
+
+fn main() {
+    let input = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "examples/rc.png".to_string());
+    let output = env::temp_dir().join("nonogrid_solution.png");
+
+    let parser = ImageParser::read_image(&input, ImageParser::DEFAULT_THRESHOLD)
+        .expect("Cannot read the image");
+    let board = parser.parse_rc::<BinaryBlock>();
+
+    #[cfg(not(feature = "sat"))]
+    {
+        let backtracking =
+            nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(1), None, None)
+                .expect("The puzzle should be solvable");
+        if let Some(solution) =
+            backtracking.and_then(|backtracking| backtracking.solutions.into_iter().next())
+        {
+            Board::restore_with_callback(&board, solution);
+        }
+    }
+    #[cfg(feature = "sat")]
+    {
+        let solutions = nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), Some(1))
+            .expect("The puzzle should be solvable");
+        if let Some(solution) = solutions.and_then(|mut solutions| solutions.next()) {
+            Board::restore_with_callback(&board, solution);
+        }
+    }
+    assert!(
+        board.read().is_solved_full(),
+        "The puzzle has several solutions"
+    );
+
+    PngRenderer::with_board(board)
+        .render_to_path(&output, 1)
+        .expect("Cannot save the image");
+    println!("The solution saved into {:?}", output);
+
+    let original = image::open(&input).expect("Cannot read the image");
+    let solved = image::open(&output).expect("Cannot read the solution");
+    assert!(original.to_luma8() == solved.to_luma8());
+    println!("The solution matches the original image");
+}
@@ -35,6 +35,18 @@ where
 
     fn as_color_id(self) -> Option<ColorId>;
     fn from_color_ids(ids: &[ColorId]) -> Self;
+
+    /// The number of colors the cell still can be (1 for the solved cell).
+    #[allow(dead_code)]
+    fn candidate_count(self) -> usize {
+        self.variants().len()
+    }
+
+    /// Whether the cell still can be any of several colors.
+    #[allow(dead_code)]
+    fn is_ambiguous(self) -> bool {
+        self.candidate_count() > 1
+    }
 }
 
 pub trait Block
@@ -57,7 +69,13 @@ where
     fn color(self) -> Self::Color;
 }
 
+/// The clue of a line.
+///
+/// The blocks go in the natural reading order:
+/// from left to right for the rows and from top to bottom for the columns.
+/// Use the `reversed` to convert the clues given in the opposite order.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Description<T: Block> {
     pub vec: Vec<T>,
 }
@@ -149,9 +167,23 @@ where
             .collect()
     }
 
+    /// The same blocks in the opposite order,
+    /// e.g. to read the column clues given from bottom to top.
+    #[allow(dead_code)]
+    pub fn reversed(&self) -> Self {
+        Self {
+            vec: self.vec.iter().rev().copied().collect(),
+        }
+    }
+
+    /// The empty description means the whole line is blank.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
     /// How long should be the minimal line to contain given description?
     fn min_space(&self) -> usize {
-        if self.vec.is_empty() {
+        if self.is_empty() {
             return 0;
         }
         *Block::partial_sums(&self.vec)
@@ -161,19 +193,81 @@ where
 
     /// The number of potential block positions for given line size.
     ///
-    /// # Panics
-    /// If the specified line length is less than the minimum space to put the blocks into.
-    pub fn positions_number(&self, line_length: usize) -> usize {
-        let min_space = self.min_space();
-        assert!(line_length >= min_space);
-        line_length - min_space + 1
+    /// Returns `None` if the specified line length is less than
+    /// the minimum space to put the blocks into.
+    pub fn positions_number(&self, line_length: usize) -> Option<usize> {
+        let slack_space = line_length.checked_sub(self.min_space())?;
+        Some(slack_space + 1)
+    }
+
+    /// The only possible line if the blocks separated with the minimal gaps occupy the whole line
+    /// (or if there are no blocks at all).
+    pub fn exact_line(&self, line_length: usize) -> Option<Vec<B::Color>> {
+        if !self.is_empty() && self.min_space() != line_length {
+            return None;
+        }
+
+        let mut line = vec![B::Color::blank(); line_length];
+        for (block, start) in self.vec.iter().zip(self.block_starts()) {
+            for cell in &mut line[start..start + block.size()] {
+                *cell = block.color();
+            }
+        }
+        Some(line)
+    }
+
+    /// Whether the fully solved line produces exactly the same blocks as the description
+    pub fn matches_line(&self, line: &[B::Color]) -> bool {
+        let blank = B::Color::blank();
+        let mut blocks = self.vec.iter();
+
+        let mut index = 0;
+        while index < line.len() {
+            let color = line[index];
+            let block_size = line[index..].iter().take_while(|&&x| x == color).count();
+            index += block_size;
+
+            if color == blank {
+                continue;
+            }
+
+            match blocks.next() {
+                Some(block) if block.size() == block_size && block.color() == color => {}
+                _ => return false,
+            }
+        }
+
+        blocks.next().is_none()
+    }
+
+    /// Every arrangement of the blocks in the empty line of given length
+    /// (respecting the gaps between the same colored blocks),
+    /// as the ranges of cells occupied by every block.
+    ///
+    /// Beware that the number of placements is the binomial coefficient `C(slack + n, n)`,
+    /// where `n` is the number of blocks and `slack` is the free space in the line,
+    /// so it grows very fast for the long lines with many short blocks.
+    ///
+    /// Produces nothing if the blocks do not fit the line.
+    #[allow(dead_code)]
+    pub fn placements(&self, line_length: usize) -> impl Iterator<Item = Vec<Range<usize>>> {
+        let slack_space = self.positions_number(line_length).map(|number| number - 1);
+        Placements {
+            min_starts: self.block_starts(),
+            sizes: self.vec.iter().map(|block| block.size()).collect(),
+            slack_space: slack_space.unwrap_or(0),
+            shifts: slack_space.map(|_| vec![0; self.vec.len()]),
+        }
     }
 
     /// For every color in the given description produce a valid position range
     pub fn color_ranges(&self, line_length: usize) -> HashMap<ColorId, Range<usize>> {
         let start_indexes = self.block_starts();
         let sums = B::partial_sums(&self.vec);
-        let slack_space = self.positions_number(line_length) - 1;
+        let slack_space = self
+            .positions_number(line_length)
+            .expect("The blocks should fit the line")
+            - 1;
 
         let line_colors: Vec<_> = dedup(self.colors());
         line_colors
@@ -183,7 +277,7 @@ where
                 let mut last_index = None;
                 for (block_index, block) in self.vec.iter().enumerate() {
                     if block.color().as_color_id() == Some(color) {
-                        if first_index == None {
+                        if first_index.is_none() {
                             first_index = Some(block_index);
                         }
                         last_index = Some(block_index);
@@ -208,6 +302,7 @@ pub mod color {
     use super::HashMap;
 
     #[derive(Debug, PartialEq, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[allow(variant_size_differences)]
     pub enum ColorValue {
         // "red", "blue", "pink"
@@ -218,6 +313,10 @@ pub mod color {
         HexValue3(u16),
         // 0xFF00FF for magenta
         HexValue6(u32),
+        // 0xFF00FF80 for semi-transparent magenta
+        HexValue8(u32),
+        // rgba(0, 255, 0, 0.5) for semi-transparent green
+        RgbaQuad(u8, u8, u8, u8),
     }
 
     impl ColorValue {
@@ -241,6 +340,20 @@ pub mod color {
                 }
             }
 
+            if value.len() == 8 {
+                let hex8 = u32::from_str_radix(value, 16);
+                if let Ok(hex8) = hex8 {
+                    return Self::HexValue8(hex8);
+                }
+            }
+
+            let rgba = value
+                .strip_prefix("rgba(")
+                .and_then(|rgba| rgba.strip_suffix(')'));
+            if let Some(rgba) = rgba.and_then(Self::parse_rgba) {
+                return rgba;
+            }
+
             let rgb: Vec<_> = value.split(',').collect();
             if rgb.len() == 3 {
                 let rgb: Vec<u8> = rgb
@@ -256,11 +369,33 @@ pub mod color {
             Self::CommonName(value.to_string())
         }
 
+        /// Parse the `r, g, b, a` inner part of the CSS `rgba(...)`
+        /// where the alpha is a fraction between 0 and 1
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        fn parse_rgba(value: &str) -> Option<Self> {
+            let components: Vec<_> = value.split(',').map(str::trim).collect();
+            if let [r, g, b, alpha] = components.as_slice() {
+                let alpha: f64 = alpha.parse().ok()?;
+                if !(0.0..=1.0).contains(&alpha) {
+                    return None;
+                }
+
+                let alpha = (alpha * 255.0).round() as u8;
+                return Some(Self::RgbaQuad(
+                    r.parse().ok()?,
+                    g.parse().ok()?,
+                    b.parse().ok()?,
+                    alpha,
+                ));
+            }
+            None
+        }
+
         #[allow(clippy::cast_possible_truncation)]
         pub fn to_rgb(&self) -> (u8, u8, u8) {
             const MULTIPLIER: u8 = 0x11;
             match self {
-                Self::RgbTriplet(r, g, b) => (*r, *g, *b),
+                Self::RgbTriplet(r, g, b) | Self::RgbaQuad(r, g, b, _) => (*r, *g, *b),
                 Self::HexValue3(hex3) => {
                     let (r, gb) = (hex3 >> 8, *hex3 as u8);
                     let (g, b) = (gb >> 4, gb % (1 << 4));
@@ -273,6 +408,7 @@ pub mod color {
 
                     (r as u8, g as u8, b)
                 }
+                Self::HexValue8(hex8) => Self::HexValue6(hex8 >> 8).to_rgb(),
                 // https://www.rapidtables.com/web/color/RGB_Color.html#color-table
                 Self::CommonName(name) => match name.to_lowercase().as_str() {
                     "black" => (0, 0, 0),
@@ -295,11 +431,37 @@ pub mod color {
                 },
             }
         }
+
+        /// The color with the alpha channel (the opaque colors have the alpha of 255)
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(dead_code)]
+        pub fn to_rgba(&self) -> (u8, u8, u8, u8) {
+            let (r, g, b) = self.to_rgb();
+            let alpha = match self {
+                Self::HexValue8(hex8) => *hex8 as u8,
+                Self::RgbaQuad(_, _, _, alpha) => *alpha,
+                _opaque => u8::MAX,
+            };
+            (r, g, b, alpha)
+        }
+
+        /// The canonical `#rrggbb` form of the color
+        pub fn to_hex_string(&self) -> String {
+            let (r, g, b) = self.to_rgb();
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+
+        /// The `#RRGGBB` form of the color (always 6 uppercase digits, the alpha is dropped).
+        /// Suitable to write the palette back, e.g. into the Olsak `#d` section.
+        pub fn to_hex(&self) -> String {
+            self.to_hex_string().to_uppercase()
+        }
     }
 
     pub type ColorId = u32;
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ColorDesc {
         id: ColorId,
         name: String,
@@ -308,6 +470,10 @@ pub mod color {
     }
 
     impl ColorDesc {
+        pub fn id(&self) -> ColorId {
+            self.id
+        }
+
         /// used in `ShellRenderer`
         pub fn symbol(&self) -> String {
             self.symbol.to_string()
@@ -321,9 +487,24 @@ pub mod color {
             }
         }
 
+        /// The name the color is registered with in the palette
+        /// (unlike the `name` it never gets replaced with the common color name).
+        pub fn palette_name(&self) -> &str {
+            &self.name
+        }
+
         pub fn rgb_value(&self) -> (u8, u8, u8) {
             self.value.to_rgb()
         }
+
+        pub fn hex(&self) -> String {
+            self.value.to_hex_string()
+        }
+
+        /// Same as the `ColorValue::to_hex`.
+        pub fn hex_value(&self) -> String {
+            self.value.to_hex()
+        }
     }
 
     #[derive(Debug, Clone)]
@@ -378,7 +559,7 @@ pub mod color {
         }
 
         pub fn set_default(&mut self, color_name: &str) -> Result<(), String> {
-            if self.vec.contains_key(color_name) {
+            if self.contains(color_name) {
                 self.default_color = Some(color_name.to_string());
                 return Ok(());
             }
@@ -395,8 +576,31 @@ pub mod color {
             self.default_color.as_ref().map(String::as_str)
         }
 
+        /// All the colors ordered by their IDs
+        pub fn iter(&self) -> impl Iterator<Item = &ColorDesc> {
+            let mut colors: Vec<_> = self.vec.values().collect();
+            colors.sort_by_key(|color_desc| color_desc.id);
+            colors.into_iter()
+        }
+
+        pub fn get(&self, name: &str) -> Option<&ColorDesc> {
+            self.vec.get(name)
+        }
+
+        pub fn contains(&self, name: &str) -> bool {
+            self.vec.contains_key(name)
+        }
+
         pub fn id_by_name(&self, name: &str) -> Option<ColorId> {
-            self.vec.get(name).map(|desc| desc.id)
+            self.get(name).map(|desc| desc.id)
+        }
+
+        #[allow(dead_code)]
+        pub fn id_by_symbol(&self, symbol: char) -> Option<ColorId> {
+            self.vec
+                .values()
+                .find(|color_desc| color_desc.symbol == symbol)
+                .map(|color_desc| color_desc.id)
         }
 
         pub fn desc_by_id(&self, id: ColorId) -> Option<ColorDesc> {
@@ -446,6 +650,94 @@ pub mod color {
             self.color_with_name_value_and_symbol(name, value, next_symbol);
         }
     }
+
+    /// The palette is saved as its colors (ordered by the IDs) and the default color name.
+    #[cfg(feature = "serde")]
+    mod palette_serde {
+        use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+        use super::{ColorDesc, ColorPalette};
+
+        #[derive(Serialize)]
+        struct PaletteRef<'a> {
+            colors: Vec<&'a ColorDesc>,
+            default: Option<&'a str>,
+        }
+
+        #[derive(Deserialize)]
+        struct Palette {
+            colors: Vec<ColorDesc>,
+            default: Option<String>,
+        }
+
+        impl Serialize for ColorPalette {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                PaletteRef {
+                    colors: self.iter().collect(),
+                    default: self.get_default(),
+                }
+                .serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for ColorPalette {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let saved = Palette::deserialize(deserializer)?;
+                let mut palette = Self::new();
+                for color in saved.colors {
+                    let _prev = palette.vec.insert(color.name.clone(), color);
+                }
+                if let Some(default) = saved.default {
+                    palette.set_default(&default).map_err(D::Error::custom)?;
+                }
+                Ok(palette)
+            }
+        }
+    }
+}
+
+/// Every block is shifted from its leftmost position
+/// no less than the previous one (so the gaps are preserved).
+/// The shifts go in the lexicographic order.
+#[allow(dead_code)]
+struct Placements {
+    min_starts: Vec<usize>,
+    sizes: Vec<usize>,
+    slack_space: usize,
+    shifts: Option<Vec<usize>>,
+}
+
+impl Iterator for Placements {
+    type Item = Vec<Range<usize>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let shifts = self.shifts.as_mut()?;
+        let placement = self
+            .min_starts
+            .iter()
+            .zip(&self.sizes)
+            .zip(shifts.iter())
+            .map(|((&start, &size), &shift)| start + shift..start + shift + size)
+            .collect();
+
+        let slack_space = self.slack_space;
+        if let Some(index) = shifts.iter().rposition(|&shift| shift < slack_space) {
+            let shift = shifts[index] + 1;
+            for next_shift in &mut shifts[index..] {
+                *next_shift = shift;
+            }
+        } else {
+            self.shifts = None;
+        }
+
+        Some(placement)
+    }
 }
 
 #[cfg(test)]
@@ -522,6 +814,110 @@ mod tests {
         //                   4 . 4
         assert_eq!(ranges, vec![(1, 0..13), (2, 6..11), (4, 5..8)])
     }
+
+    #[test]
+    fn reversed() {
+        let d = Description::new(vec![BinaryBlock(3), BinaryBlock(1), BinaryBlock(2)]);
+        assert_eq!(
+            d.reversed(),
+            Description::new(vec![BinaryBlock(2), BinaryBlock(1), BinaryBlock(3)])
+        );
+        assert_eq!(d.reversed().reversed(), d);
+
+        let d = Description::new(Vec::<ColoredBlock>::new());
+        assert!(d.reversed().is_empty());
+    }
+
+    #[test]
+    fn positions_number() {
+        let d = Description::new(vec![BinaryBlock(2), BinaryBlock(1)]);
+        assert_eq!(d.positions_number(6), Some(3));
+        assert_eq!(d.positions_number(4), Some(1));
+        assert_eq!(d.positions_number(3), None);
+
+        let d = Description::new(Vec::<BinaryBlock>::new());
+        assert_eq!(d.positions_number(0), Some(1));
+
+        // no gap required between the different colors
+        let d = Description::new(vec![
+            ColoredBlock::from_size_and_color(2, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+        ]);
+        assert_eq!(d.positions_number(3), Some(1));
+        assert_eq!(d.positions_number(2), None);
+    }
+
+    #[test]
+    fn placements() {
+        let d = Description::new(vec![BinaryBlock(2), BinaryBlock(1)]);
+        let placements: Vec<_> = d.placements(5).collect();
+        assert_eq!(
+            placements,
+            vec![vec![0..2, 3..4], vec![0..2, 4..5], vec![1..3, 4..5],]
+        );
+        assert_eq!(d.placements(6).count(), 6);
+        assert_eq!(d.placements(3).count(), 0);
+
+        let d = Description::new(Vec::<BinaryBlock>::new());
+        assert_eq!(d.placements(3).collect::<Vec<_>>(), vec![vec![]]);
+
+        // no gap required between the different colors
+        let d = Description::new(vec![
+            ColoredBlock::from_size_and_color(2, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+        ]);
+        assert_eq!(d.placements(3).collect::<Vec<_>>(), vec![vec![0..2, 2..3]]);
+        assert_eq!(d.placements(4).count(), 3);
+    }
+
+    #[test]
+    fn exact_line() {
+        use crate::block::binary::BinaryColor::{Black as B, White as W};
+
+        let d = Description::new(vec![BinaryBlock(2), BinaryBlock(1)]);
+        assert_eq!(d.exact_line(4), Some(vec![B, B, W, B]));
+        assert_eq!(d.exact_line(5), None);
+
+        let d = Description::new(Vec::<BinaryBlock>::new());
+        assert_eq!(d.exact_line(3), Some(vec![W; 3]));
+
+        let d = Description::new(vec![
+            ColoredBlock::from_size_and_color(2, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+        ]);
+        let colors: Vec<_> = d.exact_line(3).unwrap().into_iter().map(|c| c.0).collect();
+        assert_eq!(colors, vec![4, 4, 8]);
+    }
+
+    #[test]
+    fn matches_line_binary() {
+        use crate::block::binary::BinaryColor::{Black as B, White as W};
+
+        let d = Description::new(vec![BinaryBlock(2), BinaryBlock(1)]);
+        assert!(d.matches_line(&[B, B, W, B]));
+        assert!(d.matches_line(&[W, B, B, W, W, B, W]));
+        assert!(!d.matches_line(&[B, B, B, W]));
+        assert!(!d.matches_line(&[B, B, W, W]));
+        assert!(!d.matches_line(&[B, W, B, W, B]));
+    }
+
+    #[test]
+    fn matches_line_colored() {
+        use crate::block::multicolor::MultiColor;
+
+        let w = MultiColor(1);
+        let r = MultiColor(2);
+        let g = MultiColor(4);
+
+        let d = Description::new(vec![
+            ColoredBlock::from_size_and_color(2, 2),
+            ColoredBlock::from_size_and_color(1, 4),
+        ]);
+        assert!(d.matches_line(&[r, r, g, w]));
+        assert!(d.matches_line(&[w, r, r, w, g]));
+        assert!(!d.matches_line(&[r, r, r, w]));
+        assert!(!d.matches_line(&[g, r, r, w]));
+    }
 }
 
 #[cfg(test)]
@@ -549,6 +945,53 @@ mod color_value_tests {
         );
     }
 
+    #[test]
+    fn construct_with_alpha() {
+        assert_eq!(
+            ColorValue::parse("#FF00FF80"),
+            ColorValue::HexValue8(0xFF00_FF80)
+        );
+        assert_eq!(
+            ColorValue::parse("rgba(0, 255, 0, 0.5)"),
+            ColorValue::RgbaQuad(0, 255, 0, 128)
+        );
+        assert_eq!(
+            ColorValue::parse("rgba(10,20,30,1)"),
+            ColorValue::RgbaQuad(10, 20, 30, 255)
+        );
+        // invalid alpha: out of range
+        assert_eq!(
+            ColorValue::parse("rgba(0, 255, 0, 1.5)"),
+            ColorValue::CommonName("rgba(0, 255, 0, 1.5)".to_string())
+        );
+        // invalid alpha: not a number
+        assert_eq!(
+            ColorValue::parse("rgba(0, 255, 0, X)"),
+            ColorValue::CommonName("rgba(0, 255, 0, X)".to_string())
+        );
+        // invalid hex
+        assert_eq!(
+            ColorValue::parse("#FF00FFXX"),
+            ColorValue::CommonName("FF00FFXX".to_string())
+        );
+    }
+
+    #[test]
+    fn rgba() {
+        assert_eq!(ColorValue::parse("0F0").to_rgba(), (0, 255, 0, 255));
+        assert_eq!(ColorValue::parse("red").to_rgba(), (255, 0, 0, 255));
+        assert_eq!(ColorValue::parse("#FF00FF80").to_rgba(), (255, 0, 255, 128));
+        assert_eq!(ColorValue::parse("#FF00FF80").to_rgb(), (255, 0, 255));
+        assert_eq!(
+            ColorValue::parse("rgba(0, 255, 0, 0)").to_rgba(),
+            (0, 255, 0, 0)
+        );
+        assert_eq!(
+            ColorValue::parse("rgba(0, 255, 0, 0)").to_rgb(),
+            (0, 255, 0)
+        );
+    }
+
     #[test]
     fn rgb() {
         assert_eq!(ColorValue::parse("0F0").to_rgb(), (0, 255, 0));
@@ -564,4 +1007,55 @@ mod color_value_tests {
             ColorValue::parse("5b8").to_rgb()
         );
     }
+
+    #[test]
+    fn hex_string() {
+        assert_eq!(ColorValue::parse("0F0").to_hex_string(), "#00ff00");
+        assert_eq!(ColorValue::parse("0000FF").to_hex_string(), "#0000ff");
+        assert_eq!(ColorValue::parse("red").to_hex_string(), "#ff0000");
+        assert_eq!(ColorValue::parse("teal").to_hex_string(), "#008080");
+        assert_eq!(ColorValue::parse("unknown").to_hex_string(), "#000000");
+        assert_eq!(ColorValue::parse("200, 16,0  ").to_hex_string(), "#c81000");
+        // short form
+        assert_eq!(
+            ColorValue::parse("55bb88").to_hex_string(),
+            ColorValue::parse("5b8").to_hex_string()
+        );
+    }
+
+    #[test]
+    fn hex_upper() {
+        assert_eq!(ColorValue::parse("0f0").to_hex(), "#00FF00");
+        assert_eq!(ColorValue::parse("5b8").to_hex(), "#55BB88");
+        assert_eq!(ColorValue::parse("navy").to_hex(), "#000080");
+        assert_eq!(ColorValue::parse("c8100080").to_hex(), "#C81000");
+        assert_eq!(ColorValue::parse("rgba(1, 2, 3, 0.5)").to_hex(), "#010203");
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::color::{ColorPalette, ColorValue};
+
+    #[test]
+    fn get_by_name() {
+        let mut palette = ColorPalette::with_white_and_black("W", "B");
+        palette.color_with_name_and_value("r", ColorValue::parse("red"));
+
+        assert!(palette.contains("W"));
+        assert!(palette.contains("r"));
+        assert!(!palette.contains("red"));
+        assert!(!palette.contains("g"));
+
+        assert_eq!(
+            palette.get("B").map(|desc| desc.hex()),
+            Some("#000000".into())
+        );
+        assert_eq!(
+            palette.get("r").map(|desc| desc.hex_value()),
+            Some("#FF0000".into())
+        );
+        assert_eq!(palette.get("r").map(|desc| desc.name()), Some("red"));
+        assert!(palette.get("g").is_none());
+    }
 }
@@ -5,10 +5,11 @@ use std::{
 
 use crate::block::base::{
     color::{ColorId, ColorPalette},
-    Block, Color,
+    Block, Color, Description,
 };
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryColor {
     Undefined,
     White,
@@ -17,6 +18,7 @@ pub enum BinaryColor {
     BlackOrWhite,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for BinaryColor {
     fn default() -> Self {
         Self::Undefined
@@ -86,6 +88,7 @@ impl Sub for BinaryColor {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryBlock(pub usize);
 
 impl Block for BinaryBlock {
@@ -122,11 +125,19 @@ impl fmt::Display for BinaryBlock {
     }
 }
 
+impl Description<BinaryBlock> {
+    /// The clue from the plain block sizes (the zeros are dropped).
+    #[allow(dead_code)]
+    pub fn from_clue_sizes(sizes: &[usize]) -> Self {
+        Self::new(sizes.iter().copied().map(BinaryBlock).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::block::{Block, Description};
+    use crate::block::{Block, Color, Description};
 
-    use super::BinaryBlock;
+    use super::{BinaryBlock, BinaryColor};
 
     #[test]
     fn partial_sums_empty() {
@@ -145,4 +156,20 @@ mod tests {
         let d = Description::new(vec![BinaryBlock(1), BinaryBlock(2), BinaryBlock(3)]);
         assert_eq!(BinaryBlock::partial_sums(&d.vec), vec![1, 4, 8]);
     }
+
+    #[test]
+    fn from_clue_sizes() {
+        let d = Description::from_clue_sizes(&[1, 0, 2]);
+        assert_eq!(d, Description::new(vec![BinaryBlock(1), BinaryBlock(2)]));
+        assert!(Description::<BinaryBlock>::from_clue_sizes(&[0]).is_empty());
+    }
+
+    #[test]
+    fn candidate_count() {
+        assert_eq!(BinaryColor::Black.candidate_count(), 1);
+        assert!(!BinaryColor::White.is_ambiguous());
+
+        assert_eq!(BinaryColor::Undefined.candidate_count(), 2);
+        assert!(BinaryColor::Undefined.is_ambiguous());
+    }
 }
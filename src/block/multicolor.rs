@@ -9,12 +9,13 @@ use log::debug;
 use crate::{
     block::base::{
         color::{ColorId, ColorPalette},
-        Block, Color,
+        Block, Color, Description,
     },
     utils::{from_two_powers, two_powers},
 };
 
 #[derive(Debug, PartialEq, Eq, Hash, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiColor(pub ColorId);
 
 impl Color for MultiColor {
@@ -73,6 +74,10 @@ impl Color for MultiColor {
     fn from_color_ids(ids: &[ColorId]) -> Self {
         Self(from_two_powers(ids))
     }
+
+    fn candidate_count(self) -> usize {
+        self.0.count_ones() as usize
+    }
 }
 
 impl MultiColor {
@@ -136,6 +141,7 @@ impl fmt::Display for MultiColor {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColoredBlock {
     size: usize,
     color: ColorId,
@@ -151,6 +157,12 @@ impl Block for ColoredBlock {
     type Color = MultiColor;
 
     fn from_size_and_color(size: usize, color: Option<ColorId>) -> Self {
+        if size == 0 {
+            // the empty line clue (like '0') is not required to have a color:
+            // such a block is dropped from the `Description` anyway
+            return Self::default();
+        }
+
         let color = color.expect("Color not provided for ColoredBlock");
         Self { size, color }
     }
@@ -191,11 +203,27 @@ impl fmt::Display for ColoredBlock {
     }
 }
 
+impl Description<ColoredBlock> {
+    /// The clue from the pairs of the block size and its color ID (the zero sizes are dropped).
+    #[allow(dead_code)]
+    pub fn from_colored(blocks: &[(usize, ColorId)]) -> Self {
+        Self::new(
+            blocks
+                .iter()
+                .map(|&(size, color)| {
+                    // unlike the inherent constructor, gives the default block for the zero size
+                    <ColoredBlock as Block>::from_size_and_color(size, Some(color))
+                })
+                .collect(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::block::{Block, Description};
+    use crate::block::{Block, Color, Description};
 
-    use super::ColoredBlock;
+    use super::{ColoredBlock, MultiColor};
 
     #[test]
     fn partial_sums_empty() {
@@ -203,6 +231,12 @@ mod tests {
         assert_eq!(ColoredBlock::partial_sums(&d.vec), Vec::<usize>::new());
     }
 
+    #[test]
+    fn empty_without_color() {
+        let d = Description::new(vec![<ColoredBlock as Block>::from_str_and_color("0", None)]);
+        assert!(d.is_empty());
+    }
+
     #[test]
     fn partial_sums_single() {
         let d = Description::new(vec![ColoredBlock::from_size_and_color(5, 1)]);
@@ -218,4 +252,26 @@ mod tests {
         ]);
         assert_eq!(ColoredBlock::partial_sums(&d.vec), vec![1, 4, 7]);
     }
+
+    #[test]
+    fn from_colored() {
+        let d = Description::from_colored(&[(1, 4), (0, 4), (2, 8)]);
+        assert_eq!(
+            d,
+            Description::new(vec![
+                ColoredBlock::from_size_and_color(1, 4),
+                ColoredBlock::from_size_and_color(2, 8),
+            ])
+        );
+    }
+
+    #[test]
+    fn candidate_count() {
+        assert_eq!(MultiColor(4).candidate_count(), 1);
+        assert!(!MultiColor(4).is_ambiguous());
+
+        assert_eq!(MultiColor(1 + 4 + 8).candidate_count(), 3);
+        assert_eq!(MultiColor(1 + 8).candidate_count(), 2);
+        assert!(MultiColor(1 + 8).is_ambiguous());
+    }
 }
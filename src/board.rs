@@ -12,13 +12,16 @@ use crate::{
         },
         Block, Color, Description, Line,
     },
+    solver::line::{self, LineSolver},
     utils::{
         dedup,
         rc::{mutate_ref, InteriorMutableRef, MutRc, ReadRc},
     },
 };
 
-use self::callbacks::{ChangeColorCallback, RestoreCallback, SetLineCallback};
+use self::callbacks::{
+    BatchChangeCallback, ChangeColorCallback, NewColorCallback, RestoreCallback, SetLineCallback,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord)]
 pub struct Point {
@@ -42,6 +45,49 @@ impl Point {
     }
 }
 
+/// The size of the board
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[allow(dead_code)]
+pub struct Dimensions {
+    pub width: usize,
+    pub height: usize,
+}
+
+#[allow(dead_code)]
+impl Dimensions {
+    pub const fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    /// The number of cells
+    pub const fn area(self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// The reason the `Board::overlay` refused to merge the state
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[allow(dead_code)]
+pub enum OverlayError {
+    /// The overlaid state has the `actual` number of cells instead of the `expected`
+    SizeMismatch { expected: usize, actual: usize },
+    /// The cell has no candidates common for the board and for the overlaid state
+    Conflict(Point),
+}
+
+impl fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "The overlaid state should have {} cells, found {}",
+                expected, actual
+            ),
+            Self::Conflict(point) => write!(f, "The cell {:?} has no possible colors", point),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LinePosition {
     Row(usize),
@@ -71,12 +117,33 @@ impl LinePosition {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LineDirection {
     Row,
     Column,
 }
 
+impl LineDirection {
+    /// Iterate over both the directions: rows go first, then columns.
+    ///
+    /// Combined with the `Not` (`!`) operator, which switches
+    /// between the row and the column, it allows to write
+    /// the propagation-style loops over the whole board.
+    ///
+    ///```
+    /// use nonogrid::LineDirection;
+    ///
+    /// let all: Vec<_> = LineDirection::both().collect();
+    /// assert_eq!(all, [LineDirection::Row, LineDirection::Column]);
+    ///
+    /// let orthogonal: Vec<_> = LineDirection::both().map(|direction| !direction).collect();
+    /// assert_eq!(orthogonal, [LineDirection::Column, LineDirection::Row]);
+    /// ```
+    pub fn both() -> impl Iterator<Item = Self> {
+        [Self::Row, Self::Column].iter().copied()
+    }
+}
+
 impl Not for LineDirection {
     type Output = Self;
 
@@ -103,6 +170,14 @@ mod callbacks {
     pub trait ChangeColorCallback: Fn(Point) {}
 
     impl<F> ChangeColorCallback for F where F: Fn(Point) {}
+
+    pub trait NewColorCallback<C>: Fn(Point, C) {}
+
+    impl<F, C> NewColorCallback<C> for F where F: Fn(Point, C) {}
+
+    pub trait BatchChangeCallback: Fn(&[Point]) {}
+
+    impl<F> BatchChangeCallback for F where F: Fn(&[Point]) {}
 }
 
 #[cfg(feature = "threaded")]
@@ -120,6 +195,14 @@ mod callbacks {
     pub trait ChangeColorCallback: Fn(Point) + Send + Sync {}
 
     impl<F> ChangeColorCallback for F where F: Fn(Point) + Send + Sync {}
+
+    pub trait NewColorCallback<C>: Fn(Point, C) + Send + Sync {}
+
+    impl<F, C> NewColorCallback<C> for F where F: Fn(Point, C) + Send + Sync {}
+
+    pub trait BatchChangeCallback: Fn(&[Point]) + Send + Sync {}
+
+    impl<F> BatchChangeCallback for F where F: Fn(&[Point]) + Send + Sync {}
 }
 
 pub struct Board<B>
@@ -131,15 +214,14 @@ where
     desc_cols: Vec<ReadRc<Description<B>>>,
     palette: Option<ColorPalette>,
     all_colors: Vec<ColorId>,
-    // use with caching duplicated clues
-    // https://webpbn.com/survey/caching.html
-    rows_cache_indexes: Vec<usize>,
-    cols_cache_indexes: Vec<usize>,
+    memoize_rate: bool,
     cell_rate_memo: InteriorMutableRef<HashMap<B::Color, f64>>,
     // callbacks
     on_set_line: Option<Box<dyn SetLineCallback>>,
     on_restore: Option<Box<dyn RestoreCallback>>,
     on_change_color: Option<Box<dyn ChangeColorCallback>>,
+    on_new_color: Option<Box<dyn NewColorCallback<B::Color>>>,
+    on_batch_change: Option<Box<dyn BatchChangeCallback>>,
 }
 
 impl<B> fmt::Debug for Board<B>
@@ -168,57 +250,99 @@ where
         Self::with_descriptions_and_palette(rows, columns, None)
     }
 
+    /// The number of cells allowed by the parsers:
+    /// enough for any real puzzle, but protects from allocating
+    /// the gigabytes for the malformed input.
+    pub const DEFAULT_MAX_AREA: usize = 1 << 24;
+
+    /// The size of the board is not limited.
+    /// Use the `try_with_descriptions_and_palette` for the untrusted clues.
+    #[allow(dead_code)]
     pub fn with_descriptions_and_palette(
         rows: Vec<Description<B>>,
         columns: Vec<Description<B>>,
         palette: Option<ColorPalette>,
     ) -> Self {
+        Self::try_with_descriptions_and_palette(rows, columns, palette, usize::MAX)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create the board, failing if the number of cells exceeds the `max_area`.
+    pub fn try_with_descriptions_and_palette(
+        rows: Vec<Description<B>>,
+        columns: Vec<Description<B>>,
+        palette: Option<ColorPalette>,
+        max_area: usize,
+    ) -> Result<Self, String> {
         let height = rows.len();
         let width = columns.len();
+        match width.checked_mul(height) {
+            Some(area) if area <= max_area => {}
+            _ => {
+                return Err(format!(
+                    "The board {}x{} exceeds the maximum area of {} cells",
+                    width, height, max_area
+                ));
+            }
+        }
 
         let all_colors = Self::all_colors(&rows);
         let init = B::Color::from_color_ids(&all_colors);
         warn!("Initializing board: height={}, width={}", height, width);
         let cells = vec![init; width * height];
 
-        let uniq_indexes = |lines: &[Description<B>]| {
-            let uniq_lines: Vec<&Vec<B>> = dedup(lines.iter().map(|desc| &desc.vec));
-            if uniq_lines.len() < lines.len() {
-                warn!(
-                    "Reducing number of clues: {} --> {}",
-                    lines.len(),
-                    uniq_lines.len()
-                );
-            }
-            lines
-                .iter()
-                .map(|desc| {
-                    uniq_lines
-                        .iter()
-                        .position(|&uniq_line| uniq_line == &desc.vec)
-                        .expect("Every line should be present in unique lines")
-                })
-                .collect()
-        };
-
-        let rows_cache_indexes = uniq_indexes(&rows);
-        let cols_cache_indexes = uniq_indexes(&columns);
-
         let desc_rows = rows.into_iter().map(ReadRc::new).collect();
         let desc_cols = columns.into_iter().map(ReadRc::new).collect();
-        Self {
+        Ok(Self {
             cells,
             desc_rows,
             desc_cols,
             palette,
             all_colors,
-            rows_cache_indexes,
-            cols_cache_indexes,
+            memoize_rate: B::Color::memoize_rate(),
             cell_rate_memo: InteriorMutableRef::new(HashMap::new()),
             on_set_line: None,
             on_restore: None,
             on_change_color: None,
+            on_new_color: None,
+            on_batch_change: None,
+        })
+    }
+
+    /// Create the board with the given state of the cells (e.g. the saved partially solved one)
+    /// in the row-major order.
+    ///
+    /// Fails if the number of cells does not match the clues
+    /// or if the fully solved cells do not satisfy the clues.
+    #[allow(dead_code)]
+    pub fn from_parts(
+        rows: Vec<Description<B>>,
+        columns: Vec<Description<B>>,
+        palette: Option<ColorPalette>,
+        cells: Vec<B::Color>,
+    ) -> Result<Self, String> {
+        let mut board = Self::try_with_descriptions_and_palette(
+            rows,
+            columns,
+            palette,
+            Self::DEFAULT_MAX_AREA,
+        )?;
+
+        if cells.len() != board.cells.len() {
+            return Err(format!(
+                "The board {}x{} should have {} cells, but {} given",
+                board.width(),
+                board.height(),
+                board.cells.len(),
+                cells.len()
+            ));
         }
+
+        board.cells = cells;
+        if board.is_solved_full() && !board.verify_solution() {
+            return Err("The given solution does not match the clues".to_string());
+        }
+        Ok(board)
     }
 
     /// Clue colors describing the board more precisely than the palette
@@ -232,6 +356,10 @@ where
         dedup(colors)
     }
 
+    pub fn palette(&self) -> Option<&ColorPalette> {
+        self.palette.as_ref()
+    }
+
     pub fn desc_by_id(&self, id: ColorId) -> Option<ColorDesc> {
         self.palette
             .as_ref()
@@ -242,6 +370,12 @@ where
         self.cells.chunks(self.width())
     }
 
+    /// The columns are not contiguous in memory, so every one is copied
+    #[allow(dead_code)]
+    pub fn iter_columns(&self) -> impl Iterator<Item = Vec<B::Color>> + '_ {
+        (0..self.width()).map(move |index| self.get_column_iter(index).copied().collect())
+    }
+
     pub fn descriptions(&self, direction: LineDirection) -> &[ReadRc<Description<B>>] {
         match direction {
             LineDirection::Row => &self.desc_rows,
@@ -261,10 +395,203 @@ where
         self.desc_cols.len()
     }
 
+    #[allow(dead_code)]
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions::new(self.width(), self.height())
+    }
+
+    /// Whether the boards describe the same puzzle regardless of their cells state:
+    /// the rows and the columns clues are exactly the same
+    /// and every clue color has the same value in both palettes
+    /// (the colors are not compared if any of the boards has no palette).
+    #[allow(dead_code)]
+    pub fn clues_equal(&self, other: &Self) -> bool {
+        if self.desc_rows != other.desc_rows || self.desc_cols != other.desc_cols {
+            return false;
+        }
+
+        if self.palette.is_none() || other.palette.is_none() {
+            return true;
+        }
+
+        self.all_colors.iter().all(|&id| {
+            let rgb = |board: &Self| board.desc_by_id(id).map(|desc| desc.rgb_value());
+            rgb(self) == rgb(other)
+        })
+    }
+
+    /// The clues without the solution as a plain text readable by the `SimpleParser`
+    /// (the `faase` format): the board dimensions, the `rows` section and the `columns` section
+    /// with a line of space-separated block sizes for every row or column
+    /// (`0` for the empty line).
+    ///
+    /// The blocks of the non-default colors get the palette symbol after the size (e.g. `2%`).
+    #[allow(dead_code)]
+    pub fn to_clue_text(&self) -> String {
+        let default_id = self.palette.as_ref().and_then(|palette| {
+            palette
+                .get_default()
+                .and_then(|name| palette.id_by_name(name))
+        });
+
+        let line = |desc: &ReadRc<Description<B>>| {
+            if desc.vec.is_empty() {
+                return "0".to_string();
+            }
+
+            let blocks: Vec<_> = desc
+                .vec
+                .iter()
+                .map(|block| {
+                    let symbol = block
+                        .color()
+                        .as_color_id()
+                        .filter(|&id| Some(id) != default_id)
+                        .and_then(|id| self.desc_by_id(id))
+                        .map_or_else(String::new, |color_desc| color_desc.symbol());
+                    format!("{}{}", block.size(), symbol)
+                })
+                .collect();
+            blocks.join(" ")
+        };
+
+        let header = format!("width {}\nheight {}\n\nrows", self.width(), self.height());
+        once(header)
+            .chain(self.desc_rows.iter().map(line))
+            .chain(once("\ncolumns".to_string()))
+            .chain(self.desc_cols.iter().map(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     pub fn is_solved_full(&self) -> bool {
         self.cells.iter().copied().all(Color::is_solved)
     }
 
+    /// Whether every line can still be completed to satisfy its description.
+    /// The incomplete lines are not considered wrong unless they contradict the clues.
+    #[allow(dead_code)]
+    pub fn is_valid_partial<S>(&self) -> bool
+    where
+        S: LineSolver<BlockType = B>,
+    {
+        LineDirection::both().all(|direction| {
+            let size = match direction {
+                LineDirection::Row => self.height(),
+                LineDirection::Column => self.width(),
+            };
+
+            (0..size).all(|index| {
+                let position = LinePosition::with_direction_and_index(direction, index);
+                let line = self.get_line(position);
+                let valid = line::is_feasible::<S, _>(self.description(position), line);
+                if !valid {
+                    info!("The {:?} contradicts its description", position);
+                }
+                valid
+            })
+        })
+    }
+
+    /// Find a single cell forced by its row or column clue, and solve it.
+    /// The rest of the cells of the line are left untouched for the next call,
+    /// e.g. to show the solution step by step.
+    ///
+    /// Return `None` if no cell can be solved without guessing
+    /// (or the board contradicts its clues).
+    #[allow(dead_code)]
+    pub fn apply_next_hint<S>(&mut self) -> Option<(Point, B::Color)>
+    where
+        S: LineSolver<BlockType = B>,
+    {
+        // the inner `None` stops the search on the first contradicting line
+        let found = LineDirection::both().find_map(|direction| {
+            let size = match direction {
+                LineDirection::Row => self.height(),
+                LineDirection::Column => self.width(),
+            };
+
+            (0..size).find_map(|index| {
+                let position = LinePosition::with_direction_and_index(direction, index);
+                let line = self.get_line(position);
+                if line.iter().all(|cell| cell.is_solved()) {
+                    return None;
+                }
+
+                let solved =
+                    match line::solve::<S, _>(self.description(position), ReadRc::clone(&line)) {
+                        Ok(solved) => solved,
+                        Err(_) => return Some(None),
+                    };
+                line.iter()
+                    .zip(solved.iter())
+                    .position(|(old, new)| !old.is_solved() && new.is_solved())
+                    .map(|offset| {
+                        Some((
+                            Point::with_line_and_offset(position, offset),
+                            solved[offset],
+                        ))
+                    })
+            })
+        })?;
+        let (point, color) = found?;
+
+        info!("The hint: {:?} at {:?}", color, point);
+        self.set_color(&point, &color);
+        Some((point, color))
+    }
+
+    /// Quick check of the clues before solving:
+    /// every line description should fit into its line
+    /// and every color should occupy the same number of cells
+    /// according to the rows and to the columns.
+    ///
+    /// Return the description of the first offending line or color.
+    pub fn check_clues(&self) -> Result<(), String> {
+        let mut color_sums = HashMap::new();
+
+        for direction in LineDirection::both() {
+            let (lines_number, line_length) = match direction {
+                LineDirection::Row => (self.height(), self.width()),
+                LineDirection::Column => (self.width(), self.height()),
+            };
+
+            for index in 0..lines_number {
+                let position = LinePosition::with_direction_and_index(direction, index);
+                let desc = self.description(position);
+                if desc.positions_number(line_length).is_none() {
+                    return Err(format!(
+                        "Bad puzzle: the clue {:?} does not fit the {:?} of size {}",
+                        desc.vec, position, line_length
+                    ));
+                }
+
+                for block in &desc.vec {
+                    let sums = color_sums
+                        .entry(block.color().as_color_id())
+                        .or_insert((0, 0));
+                    match direction {
+                        LineDirection::Row => sums.0 += block.size(),
+                        LineDirection::Column => sums.1 += block.size(),
+                    }
+                }
+            }
+        }
+
+        let mut color_sums: Vec<_> = color_sums.into_iter().collect();
+        color_sums.sort_unstable_by_key(|(color, _sums)| *color);
+        for (color, (in_rows, in_columns)) in color_sums {
+            if in_rows != in_columns {
+                return Err(format!(
+                    "Bad puzzle: the rows have {} cells of color {:?}, but the columns have {}",
+                    in_rows, color, in_columns
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     fn get_row_slice(&self, index: usize) -> &[B::Color] {
         self.iter_rows().nth(index).expect("Invalid row index")
     }
@@ -323,7 +650,7 @@ where
     ///to the full solution (one color).
     fn cell_solution_rate(&self, cell: &B::Color) -> f64 {
         let colors = &self.all_colors;
-        if !B::Color::memoize_rate() {
+        if !self.memoize_rate {
             return cell.solution_rate(colors);
         }
 
@@ -332,6 +659,19 @@ where
             .or_insert_with(|| cell.solution_rate(colors))
     }
 
+    /// Whether to memoize the solution rate of every cell value.
+    ///
+    /// The memo is enabled by default for the multicolor boards.
+    /// Disabling it makes every rate to be computed from scratch,
+    /// but avoids locking the memo (e.g. when the board is shared between threads).
+    #[allow(dead_code)]
+    pub fn set_memoize_rate(&mut self, memoize: bool) {
+        self.memoize_rate = memoize;
+        if !memoize {
+            mutate_ref(&self.cell_rate_memo).clear();
+        }
+    }
+
     /// How many cells in the row with given index are known to be of particular color
     pub fn row_solution_rate(&self, index: usize) -> f64 {
         self.line_solution_rate(self.get_row_slice(index).iter(), self.width())
@@ -342,11 +682,32 @@ where
         self.line_solution_rate(self.get_column_iter(index), self.height())
     }
 
-    /// How many cells in the whole grid are known to be of particular color
+    /// How many cells in the whole grid are known to be of particular color.
+    ///
+    /// The partially narrowed multicolor cells add their fraction of the rate too,
+    /// see `completion_fraction` for the number of fully solved cells only.
     pub fn solution_rate(&self) -> f64 {
         self.line_solution_rate(self.cells.iter(), self.height() * self.width())
     }
 
+    /// The fraction of the fully solved cells in the whole grid (suitable for the progress bar).
+    ///
+    /// Unlike the `solution_rate`, a multicolor cell with some colors already excluded
+    /// is not counted until it has the single color left.
+    /// For the black-and-white puzzles both metrics are the same.
+    #[allow(dead_code)]
+    pub fn completion_fraction(&self) -> f64 {
+        let total = self.cells.len();
+        if total == 0 {
+            return 1.0;
+        }
+
+        let solved = self.cells.iter().filter(|cell| cell.is_solved()).count();
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = solved as f64 / total as f64;
+        fraction
+    }
+
     pub fn unsolved_cells(&self) -> impl Iterator<Item = Point> + '_ {
         self.iter_rows().enumerate().flat_map(|(y, row)| {
             row.iter().enumerate().filter_map(move |(x, cell)| {
@@ -364,6 +725,22 @@ where
         self.cells[self.linear_index(y, x)]
     }
 
+    /// The palette color of the solved cell.
+    /// Return `None` for the unsolved or blank cells or if the board has no palette.
+    #[allow(dead_code)]
+    pub fn color_at(&self, point: &Point) -> Option<ColorDesc> {
+        let cell = self.cell(point);
+        if !cell.is_solved() {
+            return None;
+        }
+
+        let id = Self::solved_cell_id(cell);
+        if id == ColorPalette::WHITE_ID {
+            return None;
+        }
+        self.desc_by_id(id)
+    }
+
     /// For the given cell yield
     /// the four possible neighbour cells.
     /// When the given cell is on a border,
@@ -394,13 +771,6 @@ where
             .into_iter()
             .filter(move |n| !self.cell(n).is_solved())
     }
-
-    pub fn cache_index(&self, line_pos: LinePosition) -> usize {
-        match line_pos {
-            LinePosition::Row(index) => self.rows_cache_indexes[index],
-            LinePosition::Column(index) => self.cols_cache_indexes[index],
-        }
-    }
 }
 
 impl<B> Board<B>
@@ -416,36 +786,110 @@ where
         self.cells.clone()
     }
 
-    fn restore(&mut self, cells: Vec<B::Color>) {
+    /// Replace all the cells, e.g. with the previously made snapshot or with a found solution.
+    pub fn restore(&mut self, cells: Vec<B::Color>) {
         self.cells = cells;
 
         if self.is_solved_full() {
-            // validate
-            let white = 0;
-            let black = 1;
-            let solution_matrix: Vec<_> = self
-                .iter_rows()
-                .map(|row| {
-                    row.iter()
-                        .map(|&cell| {
-                            if cell == B::Color::blank() {
-                                white
-                            } else {
-                                cell.as_color_id().unwrap_or(black)
-                            }
-                        })
-                        .collect()
-                })
-                .collect();
+            assert!(
+                self.verify_solution(),
+                "The restored solution does not match the clues"
+            );
+        }
+    }
+
+    /// Whether the board is fully solved and the clues
+    /// generated from the solution are exactly the same as the given ones.
+    pub fn verify_solution(&self) -> bool {
+        let solution_matrix = match self.solved_grid() {
+            Some(solution_matrix) => solution_matrix,
+            None => return false,
+        };
+
+        let (columns, rows) = clues_from_solution(&solution_matrix, ColorPalette::WHITE_ID);
+        let same = |expected: &[ReadRc<Description<B>>], actual: Vec<Description<B>>| {
+            expected.len() == actual.len()
+                && expected
+                    .iter()
+                    .zip(actual)
+                    .all(|(desc, actual)| **desc == actual)
+        };
+        same(&self.desc_cols, columns) && same(&self.desc_rows, rows)
+    }
+
+    /// The fully solved board as a matrix of color IDs
+    /// (the same shape `clues_from_solution` expects).
+    ///
+    /// The white cells get the `ColorPalette::WHITE_ID`
+    /// and the black cells of the black-and-white puzzle get the ID next to it.
+    /// Return `None` if the board is not solved yet.
+    pub fn solved_grid(&self) -> Option<Vec<Vec<ColorId>>> {
+        if !self.is_solved_full() {
+            return None;
+        }
+
+        Some(
+            self.iter_rows()
+                .map(|row| row.iter().map(|&cell| Self::solved_cell_id(cell)).collect())
+                .collect(),
+        )
+    }
 
-            let (columns, rows) = clues_from_solution(&solution_matrix, white);
-            let columns: Vec<_> = columns.into_iter().map(ReadRc::new).collect();
-            let rows: Vec<_> = rows.into_iter().map(ReadRc::new).collect();
-            assert_eq!(self.desc_cols, columns);
-            assert_eq!(self.desc_rows, rows);
+    /// Only the solved non-white cells with their color IDs (as in the `solved_grid`)
+    /// in the row-major order, e.g. to draw the filled cells of a sparse puzzle.
+    #[allow(dead_code)]
+    pub fn solved_points(&self) -> impl Iterator<Item = (Point, ColorId)> + '_ {
+        self.iter_rows().enumerate().flat_map(|(y, row)| {
+            row.iter().enumerate().filter_map(move |(x, &cell)| {
+                if cell.is_solved() && cell != B::Color::blank() {
+                    Some((Point::new(x, y), Self::solved_cell_id(cell)))
+                } else {
+                    None
+                }
+            })
+        })
+    }
+
+    fn solved_cell_id(cell: B::Color) -> ColorId {
+        // the ID assigned to the black by the `ColorPalette::with_white_and_black`
+        let black = ColorPalette::WHITE_ID << 1;
+        if cell == B::Color::blank() {
+            ColorPalette::WHITE_ID
+        } else {
+            cell.as_color_id().unwrap_or(black)
         }
     }
 
+    /// Put the solutions into the canonical order: compare the color IDs
+    /// (as in the `solved_grid`) of the cells in the row-major order.
+    ///
+    /// The order the solvers find the solutions in depends on the search heuristics,
+    /// so sort them to make the N-th solution the same across the runs.
+    pub fn sort_solutions(solutions: &mut [Vec<B::Color>]) {
+        solutions.sort_by_cached_key(|solution| {
+            solution
+                .iter()
+                .map(|&cell| Self::solved_cell_id(cell))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    /// The cells which differ in any of the given solutions,
+    /// i.e. the region making the puzzle non-unique.
+    #[allow(dead_code)]
+    pub fn ambiguous_cells(&self, solutions: &[Vec<B::Color>]) -> Vec<Point> {
+        let width = self.width();
+        let (first, others) = match solutions.split_first() {
+            Some(split) => split,
+            None => return vec![],
+        };
+
+        (0..first.len())
+            .filter(|&i| others.iter().any(|solution| solution[i] != first[i]))
+            .map(|i| Point::new(i % width, i / width))
+            .collect()
+    }
+
     pub fn diff(&self, other: &[B::Color]) -> Vec<Point> {
         let width = self.width();
         self.cells
@@ -463,6 +907,105 @@ where
             })
             .collect()
     }
+
+    /// Narrow every cell down to the candidates allowed both by the board and by the `other` state,
+    /// e.g. to combine the deductions made independently on the copies of the board.
+    ///
+    /// If the sizes differ or any cell has no common candidates,
+    /// return the error and leave the board intact.
+    #[allow(dead_code)]
+    pub fn overlay(&mut self, other: &[B::Color]) -> Result<(), OverlayError> {
+        if self.cells.len() != other.len() {
+            return Err(OverlayError::SizeMismatch {
+                expected: self.cells.len(),
+                actual: other.len(),
+            });
+        }
+
+        let width = self.width();
+        let cells: Result<Vec<_>, _> = self
+            .cells
+            .iter()
+            .zip(other)
+            .enumerate()
+            .map(|(i, (&current, &other))| {
+                let current_variants = current.variants();
+                let other_variants = other.variants();
+                let common: Vec<_> = current_variants
+                    .iter()
+                    .filter(|variant| other_variants.contains(variant))
+                    .copied()
+                    .collect();
+
+                match common.len() {
+                    0 => Err(OverlayError::Conflict(Point::new(i % width, i / width))),
+                    1 => Ok(common[0]),
+                    n if n == current_variants.len() => Ok(current),
+                    _ => {
+                        let ids: Vec<_> = common.iter().filter_map(|c| c.as_color_id()).collect();
+                        Ok(B::Color::from_color_ids(&ids))
+                    }
+                }
+            })
+            .collect();
+
+        self.cells = cells?;
+        Ok(())
+    }
+
+    /// The copy of the board reflected left to right:
+    /// every row is reversed along with the blocks in its clue, and the columns go in reverse order.
+    ///
+    /// The callbacks are not copied.
+    #[allow(dead_code)]
+    pub fn mirror_horizontal(&self) -> Self {
+        let mut mirrored = self.clone();
+        mirrored.desc_rows = self
+            .desc_rows
+            .iter()
+            .map(|desc| ReadRc::new(desc.reversed()))
+            .collect();
+        mirrored.desc_cols = self.desc_cols.iter().rev().cloned().collect();
+        mirrored.cells = self
+            .iter_rows()
+            .flat_map(|row| row.iter().rev().copied())
+            .collect();
+        mirrored
+    }
+
+    /// The copy of the board reflected top to bottom:
+    /// the rows go in reverse order, and every column is reversed along with the blocks in its clue.
+    ///
+    /// The callbacks are not copied.
+    #[allow(dead_code)]
+    pub fn mirror_vertical(&self) -> Self {
+        let mut mirrored = self.clone();
+        mirrored.desc_rows = self.desc_rows.iter().rev().cloned().collect();
+        mirrored.desc_cols = self
+            .desc_cols
+            .iter()
+            .map(|desc| ReadRc::new(desc.reversed()))
+            .collect();
+        mirrored.cells = self
+            .cells
+            .chunks(self.width())
+            .rev()
+            .flat_map(|row| row.iter().copied())
+            .collect();
+        mirrored
+    }
+
+    /// How many cells currently hold every color value.
+    ///
+    /// The partially solved cells are counted under their full set of candidates
+    /// (e.g. `BlackOrWhite` or the packed `MultiColor` value) rather than split between the variants.
+    #[allow(dead_code)]
+    pub fn histogram(&self) -> HashMap<B::Color, usize> {
+        self.cells.iter().fold(HashMap::new(), |mut counts, &cell| {
+            *counts.entry(cell).or_insert(0) += 1;
+            counts
+        })
+    }
 }
 
 impl<B> Board<B>
@@ -491,10 +1034,21 @@ where
         Ok(())
     }
 
-    pub fn reduce_colors(&mut self) {
+    /// Before solving, remove from every cell the colors which cannot be there
+    /// due to the positions of the first and the last block of every color in the clues.
+    ///
+    /// Return the number of narrowed cells. The black-and-white puzzles
+    /// (with only one non-white color) are never changed, so it is always zero for them.
+    pub fn reduce_colors(&mut self) -> usize {
+        self.reduce_colors_explained().len()
+    }
+
+    /// Same as the `reduce_colors`, but return every narrowed cell
+    /// along with the colors removed from it (sorted by ID), e.g. to show the pruning in the UI.
+    pub fn reduce_colors_explained(&mut self) -> Vec<(Point, Vec<ColorId>)> {
         // ignore [WHITE] and [WHITE, SINGLE_COLOR] cases
         if self.all_colors.len() <= 2 {
-            return;
+            return vec![];
         }
 
         let width = self.width();
@@ -564,6 +1118,7 @@ where
             })
             .collect();
 
+        let mut narrowed = vec![];
         for (y, new_row) in updated_cells.iter().enumerate() {
             for (x, &new_color) in new_row.iter().enumerate() {
                 let point = Point::new(x, y);
@@ -575,9 +1130,20 @@ where
                         point, current_color, new_color
                     );
                     self.set_color(&point, &new_color);
+
+                    let remaining = new_color.variants();
+                    let mut removed: Vec<_> = current_color
+                        .variants()
+                        .into_iter()
+                        .filter(|color| !remaining.contains(color))
+                        .filter_map(Color::as_color_id)
+                        .collect();
+                    removed.sort_unstable();
+                    narrowed.push((point, removed));
                 }
             }
         }
+        narrowed
     }
 }
 
@@ -597,6 +1163,23 @@ where
     pub fn set_callback_on_change_color<CB: ChangeColorCallback + 'static>(&mut self, f: CB) {
         self.on_change_color = Some(Box::new(f));
     }
+
+    /// Same as `set_callback_on_change_color`, but the callback
+    /// also receives the new value of the changed cell,
+    /// so there is no need to read the board again.
+    pub fn set_callback_on_new_color<CB: NewColorCallback<B::Color> + 'static>(&mut self, f: CB) {
+        self.on_new_color = Some(Box::new(f));
+    }
+
+    /// Unlike the per-line or per-cell callbacks, this one fires
+    /// only once per propagation sweep with all the changed points,
+    /// so the expensive actions (like rendering) can be done less often.
+    ///
+    /// The probes are not reported, as the board is restored right after every one of them.
+    /// The backtracking guesses are reported, and then undone with the restore callback.
+    pub fn set_callback_on_batch_change<CB: BatchChangeCallback + 'static>(&mut self, f: CB) {
+        self.on_batch_change = Some(Box::new(f));
+    }
 }
 
 impl<B> Board<B>
@@ -626,9 +1209,7 @@ where
 
     pub fn set_color_with_callback(self_: &MutRc<Self>, point: &Point, color: &B::Color) {
         self_.write().set_color(point, color);
-        if let Some(f) = &self_.read().on_change_color {
-            f(*point);
-        }
+        self_.read().notify_color_changed(point);
     }
 
     pub fn unset_color_with_callback(
@@ -637,10 +1218,23 @@ where
         color: &B::Color,
     ) -> Result<(), String> {
         self_.write().unset_color(point, color)?;
-        if let Some(f) = &self_.read().on_change_color {
+        self_.read().notify_color_changed(point);
+        Ok(())
+    }
+
+    pub(crate) fn notify_batch_change(&self, points: &[Point]) {
+        if let Some(f) = &self.on_batch_change {
+            f(points);
+        }
+    }
+
+    fn notify_color_changed(&self, point: &Point) {
+        if let Some(f) = &self.on_change_color {
             f(*point);
         }
-        Ok(())
+        if let Some(f) = &self.on_new_color {
+            f(*point, self.cell(point));
+        }
     }
 }
 
@@ -656,24 +1250,104 @@ where
             palette: self.palette.clone(),
             all_colors: self.all_colors.clone(),
             // ignore caches while cloning
-            rows_cache_indexes: self.rows_cache_indexes.clone(),
-            cols_cache_indexes: self.cols_cache_indexes.clone(),
+            memoize_rate: self.memoize_rate,
             cell_rate_memo: InteriorMutableRef::new(HashMap::new()),
             on_set_line: None,
             on_restore: None,
             on_change_color: None,
+            on_new_color: None,
+            on_batch_change: None,
+        }
+    }
+}
+
+/// The snapshot of the board (e.g. to save the partially solved puzzle and resume later):
+/// the clues, the palette and the cells, the callbacks are not saved.
+#[cfg(feature = "serde")]
+mod snapshot {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Block, Board, ColorPalette, Description};
+
+    #[derive(Serialize)]
+    #[serde(bound = "B: Serialize, B::Color: Serialize")]
+    struct SnapshotRef<'a, B: Block> {
+        rows: Vec<&'a Description<B>>,
+        columns: Vec<&'a Description<B>>,
+        palette: Option<&'a ColorPalette>,
+        cells: &'a [B::Color],
+    }
+
+    #[derive(Deserialize)]
+    #[serde(bound = "B: Deserialize<'de>, B::Color: Deserialize<'de>")]
+    struct Snapshot<B: Block> {
+        rows: Vec<Description<B>>,
+        columns: Vec<Description<B>>,
+        #[serde(default)]
+        palette: Option<ColorPalette>,
+        cells: Vec<B::Color>,
+    }
+
+    impl<B> Serialize for Board<B>
+    where
+        B: Block + Serialize,
+        B::Color: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            SnapshotRef {
+                rows: self.desc_rows.iter().map(AsRef::as_ref).collect(),
+                columns: self.desc_cols.iter().map(AsRef::as_ref).collect(),
+                palette: self.palette.as_ref(),
+                cells: &self.cells,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, B> Deserialize<'de> for Board<B>
+    where
+        B: Block + Deserialize<'de>,
+        B::Color: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let snapshot = Snapshot::deserialize(deserializer)?;
+            Self::from_parts(
+                snapshot.rows,
+                snapshot.columns,
+                snapshot.palette,
+                snapshot.cells,
+            )
+            .map_err(D::Error::custom)
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::block::{
-        binary::{BinaryBlock, BinaryColor::Undefined},
-        Description,
+    use crate::{
+        block::{
+            base::{
+                clues_from_solution,
+                color::{ColorPalette, ColorValue},
+            },
+            binary::{
+                BinaryBlock,
+                BinaryColor::{Black, BlackOrWhite, Undefined, White},
+            },
+            multicolor::{ColoredBlock, MultiColor},
+            Description,
+        },
+        solver::line::DynamicSolver,
+        utils::rc::MutRc,
     };
 
-    use super::Board;
+    use super::{Board, Dimensions, LineDirection, OverlayError, Point};
 
     #[test]
     fn u_letter() {
@@ -693,9 +1367,72 @@ mod tests {
 
         let board = Board::with_descriptions(rows, columns);
         assert_eq!(board.cells.len(), 9);
+        assert_eq!(board.dimensions(), Dimensions::new(3, 3));
         assert_eq!(board.get_row(0), vec![Undefined; 3].into());
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_snapshot() {
+        let mut board = binary_board();
+        board.restore(vec![Black, Undefined, White, Black]);
+
+        let json = serde_json::to_string(&board).unwrap();
+        let restored: Board<BinaryBlock> = serde_json::from_str(&json).unwrap();
+        assert!(restored.clues_equal(&board));
+        assert_eq!(restored.make_snapshot(), board.make_snapshot());
+        assert_eq!(restored.all_colors, board.all_colors);
+
+        let wrong_size = json.replace(r#""Undefined","#, "");
+        assert!(serde_json::from_str::<Board<BinaryBlock>>(&wrong_size)
+            .unwrap_err()
+            .to_string()
+            .contains("should have 4 cells, but 3 given"));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_snapshot_colored() {
+        let mut palette = ColorPalette::with_white_and_black("W", "B");
+        palette.color_with_name_value_and_symbol("r", ColorValue::parse("f00"), '%');
+        palette.color_with_name_value_and_symbol("g", ColorValue::parse("green"), '*');
+        let red = palette.id_by_name("r").unwrap();
+        let green = palette.id_by_name("g").unwrap();
+
+        let (columns, rows) = clues_from_solution(&[vec![red, green], vec![green, 0]], 0);
+        let mut board =
+            Board::<ColoredBlock>::with_descriptions_and_palette(rows, columns, Some(palette));
+        board.restore(vec![
+            MultiColor(red),
+            MultiColor(green),
+            MultiColor(green),
+            MultiColor(ColorPalette::WHITE_ID | red),
+        ]);
+
+        let json = serde_json::to_string(&board).unwrap();
+        let restored: Board<ColoredBlock> = serde_json::from_str(&json).unwrap();
+        assert!(restored.clues_equal(&board));
+        assert_eq!(restored.make_snapshot(), board.make_snapshot());
+
+        let palette = restored.palette().unwrap();
+        assert_eq!(palette.get_default(), Some("B"));
+        assert_eq!(palette.id_by_symbol('%'), Some(red));
+        let green_desc = restored.desc_by_id(green).unwrap();
+        assert_eq!(green_desc.name(), "green");
+        assert_eq!(green_desc.palette_name(), "g");
+        assert_eq!(green_desc.rgb_value(), (0, 128, 0));
+    }
+
+    #[test]
+    fn iter_columns() {
+        let mut board = binary_board();
+        board.restore(vec![Black, Black, White, Black]);
+
+        let columns: Vec<_> = board.iter_columns().collect();
+        assert_eq!(columns, vec![vec![Black, White], vec![Black, Black]]);
+        assert_eq!(board.iter_rows().count(), columns[0].len());
+    }
+
     #[test]
     fn i_letter() {
         // X
@@ -714,9 +1451,593 @@ mod tests {
 
         let board = Board::with_descriptions(rows, columns);
         assert_eq!(board.cells.len(), 5);
+        assert_eq!(board.dimensions(), Dimensions::new(1, 5));
+        assert_eq!(board.dimensions().area(), 5);
         assert_eq!(board.get_row(0), vec![Undefined].into());
         assert_eq!(board.desc_rows[0].vec, vec![BinaryBlock(1)]);
         assert_eq!(board.desc_rows[1].vec, vec![]);
         assert_eq!(board.desc_rows[2].vec, vec![BinaryBlock(1)]);
     }
+
+    #[test]
+    fn apply_next_hint() {
+        // X X X
+        // X . .
+        // X . X
+        let solution = [vec![1, 1, 1], vec![1, 0, 0], vec![1, 0, 1]];
+        let (columns, rows) = clues_from_solution(&solution, 0);
+        let mut board: Board<BinaryBlock> = Board::with_descriptions(rows, columns);
+
+        assert_eq!(
+            board.apply_next_hint::<DynamicSolver<_>>(),
+            Some((Point::new(0, 0), Black))
+        );
+        assert_eq!(board.cell(&Point::new(0, 0)), Black);
+        assert_eq!(board.cell(&Point::new(1, 0)), Undefined);
+
+        let mut hints = 1;
+        while board.apply_next_hint::<DynamicSolver<_>>().is_some() {
+            hints += 1;
+        }
+        assert_eq!(hints, 9);
+        assert_eq!(
+            board.solved_grid(),
+            Some(vec![vec![2, 2, 2], vec![2, 1, 1], vec![2, 1, 2]])
+        );
+    }
+
+    #[test]
+    fn apply_next_hint_contradiction() {
+        // X X X
+        // X . .
+        // X . X
+        let solution = [vec![1, 1, 1], vec![1, 0, 0], vec![1, 0, 1]];
+        let (columns, rows) = clues_from_solution(&solution, 0);
+        let mut board: Board<BinaryBlock> = Board::with_descriptions(rows, columns);
+
+        // the first row cannot be solved anymore, while the last one still gives a hint
+        board.set_color(&Point::new(0, 0), &White);
+        assert_eq!(board.apply_next_hint::<DynamicSolver<_>>(), None);
+        assert_eq!(board.cell(&Point::new(0, 2)), Undefined);
+    }
+
+    #[test]
+    fn to_clue_text() {
+        // a a .
+        // . . X
+        let rows = vec![colored(&[(2, 4)]), colored(&[(1, 2)])];
+        let columns = vec![colored(&[(1, 4)]), colored(&[(1, 4)]), colored(&[(1, 2)])];
+
+        let mut palette = ColorPalette::with_white_and_black("W", "B");
+        palette.color_with_name_and_value("a", ColorValue::parse("red"));
+        let board = Board::with_descriptions_and_palette(rows, columns, Some(palette));
+        let a = board.desc_by_id(4).unwrap().symbol();
+
+        assert_eq!(
+            board.to_clue_text(),
+            format!(
+                "width 3\nheight 2\n\nrows\n2{a}\n1\n\ncolumns\n1{a}\n1{a}\n1",
+                a = a
+            )
+        );
+    }
+
+    #[test]
+    fn clues_equal() {
+        let (rows, columns) = two_color_clues();
+
+        let palette = |second: &str| {
+            let mut palette = ColorPalette::with_white_and_black("W", "B");
+            palette.color_with_name_and_value("a", ColorValue::parse("red"));
+            palette.color_with_name_and_value("b", ColorValue::parse(second));
+            palette
+        };
+
+        let board = Board::with_descriptions_and_palette(
+            rows.clone(),
+            columns.clone(),
+            Some(palette("green")),
+        );
+
+        let mut solved = Board::with_descriptions(rows.clone(), columns.clone());
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        solved.restore(vec![a, a, w, w, b, b]);
+        assert!(board.clues_equal(&solved));
+
+        let same_colors = Board::with_descriptions_and_palette(
+            rows.clone(),
+            columns.clone(),
+            Some(palette("green")),
+        );
+        assert!(board.clues_equal(&same_colors));
+
+        let other_colors = Board::with_descriptions_and_palette(
+            rows.clone(),
+            columns.clone(),
+            Some(palette("blue")),
+        );
+        assert!(!board.clues_equal(&other_colors));
+
+        let transposed = Board::with_descriptions(columns, rows);
+        assert!(!board.clues_equal(&transposed));
+    }
+
+    #[test]
+    fn new_color_callback() {
+        let rows = vec![
+            Description::new(vec![BinaryBlock(1)]),
+            Description::new(vec![BinaryBlock(1)]),
+        ];
+        let columns = vec![Description::new(vec![BinaryBlock(1)]); 2];
+
+        let board = MutRc::new(Board::with_descriptions(rows, columns));
+        let changes = MutRc::new(vec![]);
+        {
+            let changes = changes.clone();
+            board
+                .write()
+                .set_callback_on_new_color(move |point, color| {
+                    changes.write().push((point, color))
+                });
+        }
+
+        Board::set_color_with_callback(&board, &Point::new(0, 0), &Black);
+        Board::unset_color_with_callback(&board, &Point::new(1, 0), &Black).unwrap();
+
+        assert_eq!(
+            *changes.read(),
+            vec![(Point::new(0, 0), Black), (Point::new(1, 0), White)]
+        );
+    }
+
+    #[test]
+    fn valid_partial() {
+        // X X
+        // X .
+        let rows = vec![
+            Description::new(vec![BinaryBlock(2)]),
+            Description::new(vec![BinaryBlock(1)]),
+        ];
+        let columns = vec![
+            Description::new(vec![BinaryBlock(2)]),
+            Description::new(vec![BinaryBlock(1)]),
+        ];
+
+        let board = MutRc::new(Board::with_descriptions(rows, columns));
+        assert!(board.read().is_valid_partial::<DynamicSolver<_>>());
+
+        // incomplete but still valid
+        Board::set_color_with_callback(&board, &Point::new(0, 0), &Black);
+        assert!(board.read().is_valid_partial::<DynamicSolver<_>>());
+
+        // the first row should be fully black
+        Board::set_color_with_callback(&board, &Point::new(1, 0), &White);
+        assert!(!board.read().is_valid_partial::<DynamicSolver<_>>());
+    }
+
+    fn colored(blocks: &[(usize, u32)]) -> Description<ColoredBlock> {
+        Description::new(
+            blocks
+                .iter()
+                .map(|&(size, color)| ColoredBlock::from_size_and_color(size, color))
+                .collect(),
+        )
+    }
+
+    type Clues = Vec<Description<ColoredBlock>>;
+
+    /// The rows and columns of the `two_color_board`
+    fn two_color_clues() -> (Clues, Clues) {
+        let rows = vec![colored(&[(2, 4)]), colored(&[(2, 8)])];
+        let columns = vec![
+            colored(&[(1, 4)]),
+            colored(&[(1, 4), (1, 8)]),
+            colored(&[(1, 8)]),
+        ];
+        (rows, columns)
+    }
+
+    /// a a .
+    /// . b b
+    fn two_color_board() -> Board<ColoredBlock> {
+        let (rows, columns) = two_color_clues();
+        Board::with_descriptions(rows, columns)
+    }
+
+    /// The rows and columns of the `single_cells_board`
+    fn single_cells_clues() -> (Clues, Clues) {
+        let rows = vec![colored(&[(1, 4)]), colored(&[(1, 8)])];
+        let columns = vec![colored(&[(1, 4)]), colored(&[]), colored(&[(1, 8)])];
+        (rows, columns)
+    }
+
+    /// a . .
+    /// . . b
+    fn single_cells_board() -> Board<ColoredBlock> {
+        let (rows, columns) = single_cells_clues();
+        Board::with_descriptions(rows, columns)
+    }
+
+    /// X X
+    /// . X
+    fn binary_board() -> Board<BinaryBlock> {
+        let rows = vec![
+            Description::new(vec![BinaryBlock(2)]),
+            Description::new(vec![BinaryBlock(1)]),
+        ];
+        let columns = vec![
+            Description::new(vec![BinaryBlock(1)]),
+            Description::new(vec![BinaryBlock(2)]),
+        ];
+        Board::with_descriptions(rows, columns)
+    }
+
+    #[test]
+    fn reduce_colors_single_row() {
+        // a . b
+        let rows = vec![colored(&[(1, 4), (1, 8)])];
+        let columns = vec![colored(&[(1, 4)]), colored(&[]), colored(&[(1, 8)])];
+
+        let mut board = Board::with_descriptions(rows, columns);
+        assert_eq!(board.reduce_colors(), 3);
+        assert_eq!(
+            board.make_snapshot(),
+            vec![MultiColor(4 + 1), MultiColor(1), MultiColor(8 + 1)]
+        );
+    }
+
+    #[test]
+    fn reduce_colors_explained() {
+        // a . b
+        let rows = vec![colored(&[(1, 4), (1, 8)])];
+        let columns = vec![colored(&[(1, 4)]), colored(&[]), colored(&[(1, 8)])];
+
+        let mut board = Board::with_descriptions(rows, columns);
+        assert_eq!(
+            board.reduce_colors_explained(),
+            vec![
+                (Point::new(0, 0), vec![8]),
+                (Point::new(1, 0), vec![4, 8]),
+                (Point::new(2, 0), vec![4]),
+            ]
+        );
+        assert!(board.reduce_colors_explained().is_empty());
+    }
+
+    #[test]
+    fn reduce_colors_single_column() {
+        // a
+        // b
+        // b
+        let rows = vec![colored(&[(1, 4)]), colored(&[(1, 8)]), colored(&[(1, 8)])];
+        let columns = vec![colored(&[(1, 4), (2, 8)])];
+
+        let mut board = Board::with_descriptions(rows, columns);
+        assert_eq!(board.reduce_colors(), 3);
+        assert_eq!(
+            board.make_snapshot(),
+            vec![MultiColor(4 + 1), MultiColor(8 + 1), MultiColor(8 + 1)]
+        );
+    }
+
+    #[test]
+    fn histogram() {
+        let mut board = two_color_board();
+        let unsolved = MultiColor(1 + 4 + 8);
+        assert_eq!(
+            board.histogram().into_iter().collect::<Vec<_>>(),
+            [(unsolved, 6)]
+        );
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, a, w, w, b, b]);
+        let mut histogram: Vec<_> = board.histogram().into_iter().collect();
+        histogram.sort_by_key(|(color, _count)| color.0);
+        assert_eq!(histogram, [(w, 2), (a, 2), (b, 2)]);
+    }
+
+    #[test]
+    fn solution_rate_without_memo() {
+        let mut board = two_color_board();
+        board.restore(vec![
+            MultiColor(4),
+            MultiColor(4),
+            MultiColor(1 + 4 + 8),
+            MultiColor(1 + 4),
+            MultiColor(8),
+            MultiColor(8),
+        ]);
+        let memoized = board.solution_rate();
+
+        board.set_memoize_rate(false);
+        assert!((board.solution_rate() - memoized).abs() < f64::EPSILON);
+        assert!(board.solution_rate() > 0.0 && board.solution_rate() < 1.0);
+    }
+
+    #[test]
+    fn solved_grid() {
+        let mut board = two_color_board();
+        assert_eq!(board.solved_grid(), None);
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, a, w, w, b, b]);
+        assert_eq!(
+            board.solved_grid(),
+            Some(vec![vec![4, 4, 1], vec![1, 8, 8]])
+        );
+    }
+
+    #[test]
+    fn color_at() {
+        let (rows, columns) = single_cells_clues();
+
+        let mut palette = ColorPalette::with_white_and_black("W", "B");
+        palette.color_with_name_and_value("a", ColorValue::parse("red"));
+        palette.color_with_name_and_value("b", ColorValue::parse("#00FF00"));
+        let mut board = Board::with_descriptions_and_palette(rows, columns, Some(palette));
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, w, w, w, MultiColor(1 + 8), b]);
+
+        let red = board.color_at(&Point::new(0, 0)).unwrap();
+        assert_eq!(red.name(), "red");
+        assert_eq!(red.rgb_value(), (255, 0, 0));
+        assert_eq!(red.symbol(), board.desc_by_id(4).unwrap().symbol());
+
+        let green = board.color_at(&Point::new(2, 1)).unwrap();
+        assert_eq!(green.hex(), "#00ff00");
+
+        // blank
+        assert!(board.color_at(&Point::new(1, 0)).is_none());
+        // unsolved
+        assert!(board.color_at(&Point::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn solved_points() {
+        let mut board = single_cells_board();
+        assert_eq!(board.solved_points().count(), 0);
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, w, w, w, MultiColor(1 + 8), b]);
+        assert_eq!(
+            board.solved_points().collect::<Vec<_>>(),
+            vec![(Point::new(0, 0), 4), (Point::new(2, 1), 8)]
+        );
+    }
+
+    #[test]
+    fn completion_fraction() {
+        let mut board = single_cells_board();
+        assert_eq!(board.completion_fraction(), 0.0);
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, w, w, w, MultiColor(1 + 8), MultiColor(1 + 4 + 8)]);
+        assert!((board.completion_fraction() - 4.0 / 6.0).abs() < f64::EPSILON);
+        // the narrowed cells are partially counted in the solution rate
+        assert!(board.solution_rate() > board.completion_fraction());
+
+        board.restore(vec![a, w, w, w, w, b]);
+        assert_eq!(board.completion_fraction(), 1.0);
+    }
+
+    #[test]
+    fn mirror() {
+        let mut board = two_color_board();
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, a, w, w, b, b]);
+
+        let horizontal = board.mirror_horizontal();
+        assert_eq!(horizontal.make_snapshot(), vec![w, a, a, b, b, w]);
+        assert_eq!(
+            *horizontal.descriptions(LineDirection::Column)[0],
+            colored(&[(1, 8)])
+        );
+        assert!(horizontal.verify_solution());
+
+        let vertical = board.mirror_vertical();
+        assert_eq!(vertical.make_snapshot(), vec![w, b, b, a, a, w]);
+        assert_eq!(
+            *vertical.descriptions(LineDirection::Column)[1],
+            colored(&[(1, 8), (1, 4)])
+        );
+        assert!(vertical.verify_solution());
+
+        let back = horizontal.mirror_horizontal();
+        assert!(back.clues_equal(&board));
+        assert!(!back.differs(&board.make_snapshot()));
+    }
+
+    #[test]
+    fn verify_solution() {
+        let mut board = two_color_board();
+        assert!(!board.verify_solution());
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        board.restore(vec![a, a, w, w, b, b]);
+        assert!(board.verify_solution());
+
+        // bypass the validation in the `restore`
+        board.cells = vec![a, w, a, w, b, b];
+        assert!(!board.verify_solution());
+    }
+
+    #[test]
+    fn solved_grid_binary() {
+        let mut board = binary_board();
+        board.restore(vec![Black, Black, White, Black]);
+        assert_eq!(board.solved_grid(), Some(vec![vec![2, 2], vec![1, 2]]));
+    }
+
+    #[test]
+    fn sort_solutions() {
+        let (b, w) = (Black, White);
+        let mut solutions = vec![vec![b, w, w, b], vec![w, b, b, w], vec![b, w, b, w]];
+
+        Board::<BinaryBlock>::sort_solutions(&mut solutions);
+        assert_eq!(
+            solutions,
+            vec![vec![w, b, b, w], vec![b, w, w, b], vec![b, w, b, w]]
+        );
+    }
+
+    #[test]
+    fn from_parts() {
+        let (rows, columns) = two_color_clues();
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        let unknown = MultiColor(1 + 4 + 8);
+        let partial = vec![a, a, w, unknown, unknown, b];
+        let board =
+            Board::from_parts(rows.clone(), columns.clone(), None, partial.clone()).unwrap();
+        assert_eq!(board.make_snapshot(), partial);
+        assert!(!board.is_solved_full());
+
+        let board =
+            Board::from_parts(rows.clone(), columns.clone(), None, vec![a, a, w, w, b, b]).unwrap();
+        assert!(board.verify_solution());
+
+        // wrong number of cells
+        assert!(Board::from_parts(rows.clone(), columns.clone(), None, vec![a, a, w]).is_err());
+        // does not match the clues
+        assert!(Board::from_parts(rows, columns, None, vec![a, a, w, b, b, w]).is_err());
+    }
+
+    #[test]
+    fn max_area() {
+        let rows = vec![Description::new(vec![BinaryBlock(1)]); 3];
+        let columns = vec![Description::new(vec![BinaryBlock(1)]); 3];
+
+        let board =
+            Board::try_with_descriptions_and_palette(rows.clone(), columns.clone(), None, 9);
+        assert!(board.is_ok());
+
+        let err = Board::try_with_descriptions_and_palette(rows, columns, None, 8).unwrap_err();
+        assert_eq!(err, "The board 3x3 exceeds the maximum area of 8 cells");
+    }
+
+    #[test]
+    fn default_max_area() {
+        let side = 1 << 13;
+        let rows = vec![Description::<BinaryBlock>::new(vec![]); side];
+        let columns = vec![Description::new(vec![]); side];
+        let err = Board::try_with_descriptions_and_palette(
+            rows,
+            columns,
+            None,
+            Board::<BinaryBlock>::DEFAULT_MAX_AREA,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "The board 8192x8192 exceeds the maximum area of 16777216 cells"
+        );
+    }
+
+    #[test]
+    fn overlay() {
+        let mut board = single_cells_board();
+
+        let (w, a, b) = (MultiColor(1), MultiColor(4), MultiColor(8));
+        let all = MultiColor(1 | 4 | 8);
+        assert_eq!(board.cells, vec![all; 6]);
+
+        let partial = vec![MultiColor(1 | 4), all, all, all, MultiColor(4 | 8), w];
+        board.overlay(&partial).unwrap();
+        assert_eq!(board.cells, partial);
+
+        board.overlay(&[a, w, all, all, all, all]).unwrap();
+        let narrowed = vec![a, w, all, all, MultiColor(4 | 8), w];
+        assert_eq!(board.cells, narrowed);
+
+        // the last cell is already white, the state is kept intact on conflict
+        assert_eq!(
+            board.overlay(&[all, all, b, all, all, b]),
+            Err(OverlayError::Conflict(Point::new(2, 1)))
+        );
+        assert_eq!(board.cells, narrowed);
+    }
+
+    #[test]
+    fn overlay_binary() {
+        let mut board = binary_board();
+
+        board
+            .overlay(&[Black, Undefined, BlackOrWhite, White])
+            .unwrap();
+        assert_eq!(board.cells, vec![Black, Undefined, Undefined, White]);
+
+        assert_eq!(
+            board.overlay(&[White, Black, Undefined, Undefined]),
+            Err(OverlayError::Conflict(Point::new(0, 0)))
+        );
+        assert_eq!(
+            board.overlay(&[White, Black]),
+            Err(OverlayError::SizeMismatch {
+                expected: 4,
+                actual: 2
+            })
+        );
+        assert_eq!(board.cells, vec![Black, Undefined, Undefined, White]);
+    }
+
+    #[test]
+    fn display_binary() {
+        let mut board = binary_board();
+        assert_eq!(board.to_string(), "??\n??");
+
+        board.restore(vec![Black, Black, White, Black]);
+        assert_eq!(board.to_string(), "\u{25A0}\u{25A0}\n.\u{25A0}");
+    }
+
+    #[test]
+    fn check_clues() {
+        let board = two_color_board();
+        assert!(board.check_clues().is_ok());
+
+        // the column is too short
+        let rows = vec![colored(&[(2, 4)]), colored(&[(2, 4)])];
+        let columns = vec![colored(&[(1, 4), (1, 4)]), colored(&[(2, 4)])];
+        let board = Board::with_descriptions(rows, columns);
+        assert_eq!(
+            board.check_clues().unwrap_err(),
+            "Bad puzzle: the clue [ColoredBlock { size: 1, color: 4 }, ColoredBlock { size: 1, color: 4 }] \
+             does not fit the Column(0) of size 2"
+        );
+
+        // the colors are swapped in the columns
+        let rows = vec![colored(&[(1, 4)]), colored(&[(1, 4)])];
+        let columns = vec![colored(&[(1, 8)]), colored(&[(1, 8)])];
+        let board = Board::with_descriptions(rows, columns);
+        assert_eq!(
+            board.check_clues().unwrap_err(),
+            "Bad puzzle: the rows have 2 cells of color Some(4), but the columns have 0"
+        );
+
+        let rows = vec![Description::new(vec![BinaryBlock(2)])];
+        let columns = vec![Description::new(vec![BinaryBlock(1)]); 2];
+        let board = Board::with_descriptions(rows, columns);
+        assert!(board.check_clues().is_ok());
+
+        let columns = vec![
+            Description::new(vec![BinaryBlock(1)]),
+            Description::new(vec![]),
+        ];
+        let board = Board::with_descriptions(vec![Description::new(vec![BinaryBlock(2)])], columns);
+        assert_eq!(
+            board.check_clues().unwrap_err(),
+            "Bad puzzle: the rows have 2 cells of color None, but the columns have 1"
+        );
+    }
+
+    #[test]
+    fn reduce_colors_single_cell() {
+        let rows = vec![colored(&[(1, 4)])];
+        let columns = vec![colored(&[(1, 4)])];
+
+        let mut board = Board::with_descriptions(rows, columns);
+        // only two colors: white and 4
+        assert_eq!(board.reduce_colors(), 0);
+        assert_eq!(board.make_snapshot(), vec![MultiColor(4 + 1)]);
+    }
 }
@@ -7,7 +7,7 @@ use lru::LruCache;
 pub trait Cached<K, V> {
     fn cache_get(&mut self, key: &K) -> Option<&V>;
     fn cache_set(&mut self, key: K, val: V);
-    fn cache_remove(&mut self, k: &K) -> Option<V>;
+    #[allow(dead_code)]
     fn cache_clear(&mut self);
     fn cache_size(&self) -> usize;
     fn cache_hits(&self) -> Option<u32>;
@@ -25,6 +25,8 @@ where
 }
 
 impl<K: Hash + Eq, V> GrowableCache<K, V> {
+    /// Store at most `size` entries.
+    /// When full, the least recently used (set or got) entry is evicted by the next `cache_set`.
     pub fn with_capacity(size: usize) -> Self {
         Self {
             store: LruCache::new(size),
@@ -48,9 +50,6 @@ impl<K: Hash + Eq, V> Cached<K, V> for GrowableCache<K, V> {
     fn cache_set(&mut self, key: K, val: V) {
         let _old_value = self.store.put(key, val);
     }
-    fn cache_remove(&mut self, k: &K) -> Option<V> {
-        self.store.pop(k)
-    }
     fn cache_clear(&mut self) {
         self.store.clear();
     }
@@ -83,3 +82,26 @@ where
         (0, 0, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Cached, GrowableCache};
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut cache = GrowableCache::with_capacity(2);
+        cache.cache_set(1, "one");
+        cache.cache_set(2, "two");
+        // the 1 becomes more recent than the 2
+        assert_eq!(cache.cache_get(&1), Some(&"one"));
+
+        cache.cache_set(3, "three");
+        assert_eq!(cache.cache_size(), 2);
+        assert_eq!(cache.cache_get(&2), None);
+        assert_eq!(cache.cache_get(&1), Some(&"one"));
+        assert_eq!(cache.cache_get(&3), Some(&"three"));
+
+        assert_eq!(cache.cache_hits(), Some(3));
+        assert_eq!(cache.cache_misses(), Some(1));
+    }
+}
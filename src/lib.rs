@@ -2,6 +2,8 @@
 
 // do not warn on older Rust versions
 #![allow(unknown_lints)]
+// do not warn on newer Rust versions where some of the lints below are removed
+#![allow(renamed_and_removed_lints)]
 //
 // The following list was generated with the command
 //   $ rustc -W help | grep ' allow ' | awk '{print $1}' | tr - _ | sort | xargs -I{} echo '#![warn({})]'
@@ -63,17 +65,36 @@
 
 pub use self::{
     block::{
-        base::{color::ColorId, Block, Color, Description},
+        base::{clues_from_solution, color::ColorId, Block, Color, Description},
         binary::{BinaryBlock, BinaryColor},
         multicolor::ColoredBlock,
     },
-    board::Board,
+    board::{Board, Dimensions, LineDirection, OverlayError, Point},
     parser::{BoardParser, DetectedParser},
     solver::{
-        line::{DynamicColor, DynamicSolver as LineSolver},
-        probing::{FullProbe1 as FullProbe, ProbeSolver},
-        propagation::Solver as PropagationSolver,
-        run as solve,
+        has_unique_solution,
+        line::{
+            DynamicColor, DynamicSolver as LineSolver, HybridSolver as HybridLineSolver,
+            UnsolvableLine,
+        },
+        probing::{FullProbe1 as FullProbe, ProbeImpact, ProbeSolver},
+        propagation::{
+            set_default_cache_entries_per_line, SharedCache as SharedLineCache,
+            Solver as PropagationSolver,
+        },
+        run as solve, run_from_point as solve_from_point, run_ref as solve_ref,
+        run_spoj_stream as solve_spoj_stream,
+    },
+};
+
+#[cfg(feature = "sat")]
+pub use self::solver::DEFAULT_MAX_SAT_SOLUTIONS;
+#[cfg(not(feature = "sat"))]
+pub use self::solver::{
+    backtracking::{ChoosePixel, SearchCounters, SolveStats, Solver as BacktrackingSolver},
+    difficulty::{
+        classify as classify_difficulty, classify_with as classify_difficulty_with,
+        DifficultyThresholds, DifficultyTier,
     },
 };
 
@@ -83,6 +104,15 @@ mod cache;
 pub mod parser;
 pub mod render;
 mod solver;
-mod utils;
+#[path = "utils.rs"]
+mod utils_impl;
+
+/// The helpers for parsing the puzzle-like formats.
+pub mod utils {
+    pub use crate::utils_impl::{pad, split_sections, transpose};
+
+    // the rest of the helpers are internal
+    pub(crate) use crate::utils_impl::*;
+}
 
 pub type RcBoard<B> = utils::rc::MutRc<Board<B>>;
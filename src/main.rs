@@ -1,7 +1,9 @@
 use std::{
+    env,
     fmt::Display,
     fs,
     io::{self, stdin, Read},
+    str::FromStr,
 };
 
 use self::{
@@ -11,7 +13,7 @@ use self::{
     parser::{BoardParser, NetworkReader, ParseError, PuzzleScheme},
     render::{Renderer, ShellRenderer},
     solver::{
-        line::{DynamicColor, DynamicSolver},
+        line::{DynamicColor, DynamicSolver, HybridSolver, LineSolver},
         probing::FullProbe1,
     },
     utils::rc::MutRc,
@@ -33,7 +35,7 @@ mod cli {
         crate_authors, crate_description, crate_name, crate_version, value_t, App, Arg, ArgMatches,
     };
 
-    use super::{fs, read_stdin, ParseError, SearchOptions, Source};
+    use super::{fs, read_stdin, LineSolverKind, ParseError, SearchOptions, Source};
 
     pub(super) struct Params<'a> {
         matches: ArgMatches<'a>,
@@ -61,7 +63,13 @@ mod cli {
                 .args_from_usage(
                     "-m, --max-solutions=[THRESHOLD] 'Stop searching after finding enough solutions'
              -t, --timeout=[SECONDS] 'Stop searching after specified timeout in seconds'
-             -d, --max-depth=[DEPTH] 'Stop searching after reaching specified search depth'",
+             -d, --max-depth=[DEPTH] 'Stop searching after reaching specified search depth'
+             -s, --sort-solutions 'Show the found solutions in the canonical (row-major color IDs) order'",
+                )
+                .arg(
+                    Arg::with_name("line-solver").help("The algorithm to solve the single lines")
+                        .short("l").long("line-solver").takes_value(true)
+                        .possible_values(LineSolverKind::NAMES).default_value(LineSolverKind::NAMES[0])
                 )
                 .get_matches();
 
@@ -105,9 +113,15 @@ mod cli {
                 self.parse_arg("max-solutions"),
                 self.parse_arg("timeout"),
                 self.parse_arg("max-depth"),
+                self.matches.is_present("sort-solutions"),
             )
         }
 
+        pub(super) fn get_line_solver(&self) -> LineSolverKind {
+            let matches = &self.matches;
+            value_t!(matches, "line-solver", LineSolverKind).unwrap_or_else(|e| e.exit())
+        }
+
         fn parse_arg<T>(&self, name: &str) -> Option<T>
         where
             T: FromStr,
@@ -127,7 +141,7 @@ mod cli {
 mod cli {
     use std::env;
 
-    use super::{fs, read_stdin, ParseError, SearchOptions, Source};
+    use super::{fs, read_stdin, LineSolverKind, ParseError, SearchOptions, Source};
 
     pub(super) struct Params {
         file_name: Option<String>,
@@ -150,7 +164,12 @@ mod cli {
 
         #[allow(clippy::unused_self)]
         pub(super) const fn get_search_options(&self) -> SearchOptions {
-            (None, None, None)
+            (None, None, None, false)
+        }
+
+        #[allow(clippy::unused_self)]
+        pub(super) const fn get_line_solver(&self) -> LineSolverKind {
+            LineSolverKind::Dynamic
         }
     }
 }
@@ -166,45 +185,100 @@ fn main() -> Result<(), ParseError> {
     #[cfg(feature = "env_logger")]
     env_logger::init();
 
+    // lower it to save the memory on the huge puzzles
+    if let Some(entries) = env::var("CACHE_ENTRIES_PER_LINE")
+        .ok()
+        .and_then(|val| val.parse().ok())
+    {
+        solver::propagation::set_default_cache_entries_per_line(entries);
+    }
+
     let params = Params::new();
     let search_options = params.get_search_options();
+    let line_solver = params.get_line_solver();
     let (source, content) = params.get_content()?;
 
     match source {
         Source::LocalFile => run(
-            &parser::DetectedParser::with_content(&content)?,
+            &parser::DetectedParser::with_content_fallback(&content)?,
             search_options,
+            line_solver,
+        ),
+        Source::WebPbn => run(
+            &parser::WebPbn::read_remote(&content)?,
+            search_options,
+            line_solver,
         ),
-        Source::WebPbn => run(&parser::WebPbn::read_remote(&content)?, search_options),
         Source::NonogramsOrg => run(
             &parser::NonogramsOrg::read_remote(&content)?,
             search_options,
+            line_solver,
         ),
-    };
-    Ok(())
+    }
 }
 
-fn run<P>(board_parser: &P, search_options: SearchOptions)
+fn run<P>(
+    board_parser: &P,
+    search_options: SearchOptions,
+    line_solver: LineSolverKind,
+) -> Result<(), ParseError>
 where
     P: BoardParser,
 {
+    let color_count = board_parser.color_count()?;
+    if color_count > MANY_COLORS {
+        log::warn!(
+            "The puzzle has {} colors: the solving can be slow and require a lot of memory",
+            color_count
+        );
+    }
+
     match board_parser.infer_scheme() {
         PuzzleScheme::BlackAndWhite => {
-            run_with_block::<BinaryBlock, _>(board_parser, search_options)
+            run_with_block::<BinaryBlock, _>(board_parser, search_options, line_solver)
+        }
+        PuzzleScheme::MultiColor => {
+            run_with_block::<ColoredBlock, _>(board_parser, search_options, line_solver)
         }
-        PuzzleScheme::MultiColor => run_with_block::<ColoredBlock, _>(board_parser, search_options),
     }
 }
 
-fn run_with_block<B, P>(board_parser: &P, search_options: SearchOptions)
+fn run_with_block<B, P>(
+    board_parser: &P,
+    search_options: SearchOptions,
+    line_solver: LineSolverKind,
+) -> Result<(), ParseError>
 where
     B: 'static + Block + Display,
     B::Color: DynamicColor + Display,
     P: BoardParser,
+{
+    match line_solver {
+        LineSolverKind::Dynamic => {
+            run_with_solver::<B, DynamicSolver<B>, _>(board_parser, search_options)
+        }
+        LineSolverKind::Hybrid => {
+            run_with_solver::<B, HybridSolver<B>, _>(board_parser, search_options)
+        }
+    }
+}
+
+fn run_with_solver<B, S, P>(
+    board_parser: &P,
+    search_options: SearchOptions,
+) -> Result<(), ParseError>
+where
+    B: 'static + Block + Display,
+    B::Color: Display,
+    S: LineSolver<BlockType = B>,
+    P: BoardParser,
 {
     let board = {
-        let mut board = board_parser.parse::<B>();
-        board.reduce_colors();
+        let mut board = board_parser.build::<B>()?;
+        let narrowed = board.reduce_colors();
+        if narrowed > 0 {
+            log::warn!("Reduced the colors of {} cells before solving", narrowed);
+        }
         board
     };
     let board = MutRc::new(board);
@@ -212,19 +286,38 @@ where
 
     #[cfg(not(feature = "sat"))]
     {
-        let backtracking = solver::run::<_, DynamicSolver<_>, FullProbe1<_>>(
+        let backtracking = solver::run::<_, S, FullProbe1<_>>(
             MutRc::clone(&board),
             search_options.0,
             search_options.1,
             search_options.2,
         )
         .unwrap();
-        println!("{}", r.render());
+        r.render_to(&mut io::stdout().lock()).unwrap();
+        println!();
+
+        if let Some(mut backtracking) = backtracking {
+            if search_options.3 {
+                backtracking.sort_solutions();
+            }
+
+            if backtracking.timed_out_in_propagation() {
+                println!("The propagation was interrupted by the timeout, the solution is partial");
+            } else if backtracking.partial_solution.is_some() {
+                println!("The search was interrupted by the timeout, the solution is partial");
+            }
+
+            if backtracking.required_guessing() {
+                println!("The puzzle cannot be solved without guessing");
+            }
 
-        if let Some(backtracking) = backtracking {
             let solutions = backtracking.solutions;
             if !solutions.is_empty() && (!board.read().is_solved_full() || solutions.len() > 1) {
                 println!("Backtracking found {} solutions:", solutions.len());
+                if solutions.len() > 1 {
+                    let ambiguous = board.read().ambiguous_cells(&solutions);
+                    println!("The cells making the puzzle non-unique: {:?}", ambiguous);
+                }
                 for (i, solution) in solutions.into_iter().enumerate() {
                     if i > 0 {
                         let diff = board.read().diff(&solution);
@@ -248,16 +341,19 @@ where
 
     #[cfg(feature = "sat")]
     {
-        let sat_solutions = solver::run::<_, DynamicSolver<_>, FullProbe1<_>>(
-            MutRc::clone(&board),
-            search_options.0,
-        )
-        .unwrap();
-        println!("{}", r.render());
+        let sat_solutions =
+            solver::run::<_, S, FullProbe1<_>>(MutRc::clone(&board), search_options.0).unwrap();
+        r.render_to(&mut io::stdout().lock()).unwrap();
+        println!();
 
         if let Some(solutions) = sat_solutions {
+            let mut solutions: Vec<_> = solutions.collect();
+            if search_options.3 {
+                Board::<B>::sort_solutions(&mut solutions);
+            }
+
             let mut found = false;
-            for (i, solution) in solutions.enumerate() {
+            for (i, solution) in solutions.into_iter().enumerate() {
                 if i > 0 {
                     let diff = board.read().diff(&solution);
                     assert!(!diff.is_empty());
@@ -273,14 +369,44 @@ where
             assert!(found, "Puzzle is unsatisfied");
         }
     }
+    Ok(())
 }
 
-type SearchOptions = (Option<usize>, Option<u32>, Option<usize>);
+/// Every color of the `MultiColor` cell is a separate bit
+/// and every line solution considers every color of every cell.
+const MANY_COLORS: usize = 16;
+
+/// The max solutions, the timeout, the max depth and whether to sort the solutions.
+type SearchOptions = (Option<usize>, Option<u32>, Option<usize>, bool);
+
+/// The line solvers available to choose at runtime.
+#[derive(Debug, Clone, Copy)]
+enum LineSolverKind {
+    Dynamic,
+    Hybrid,
+}
+
+#[cfg(feature = "clap")]
+impl LineSolverKind {
+    const NAMES: &'static [&'static str] = &["dynamic", "hybrid"];
+}
+
+impl FromStr for LineSolverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dynamic" => Ok(Self::Dynamic),
+            "hybrid" => Ok(Self::Hybrid),
+            _ => Err(format!("Unknown line solver: {:?}", s)),
+        }
+    }
+}
 
+// the remote sources are chosen only with the command line arguments
+#[cfg_attr(not(feature = "clap"), allow(dead_code))]
 enum Source {
     LocalFile,
-    #[allow(dead_code)]
     WebPbn,
-    #[allow(dead_code)]
     NonogramsOrg,
 }
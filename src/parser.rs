@@ -1,7 +1,7 @@
-use std::{any::Any, fmt, fs, io, num::ParseIntError};
+use std::{any::Any, convert::TryFrom, fmt, fs, io, iter::once, num::ParseIntError, path::Path};
 
 use hashbrown::{HashMap, HashSet};
-use log::info;
+use log::{info, warn};
 
 use crate::{
     block::{
@@ -10,13 +10,19 @@ use crate::{
             color::{ColorId, ColorPalette, ColorValue},
         },
         binary::BinaryBlock,
-        Block, Description,
+        multicolor::ColoredBlock,
+        Block, Color, Description,
     },
-    board::Board,
+    board::{Board, LineDirection},
     utils::{iter::FindOk, product, rc::MutRc, split_sections},
 };
 
-pub use self::{ini::MyFormat, xml::WebPbn};
+pub use self::{ini::MyFormat, json::JsonParser, xml::WebPbn};
+
+// the command line tool does not read the images
+#[cfg(feature = "png")]
+#[allow(unused_imports)]
+pub use self::png::ImageParser;
 
 #[derive(Debug)]
 pub struct ParseError(pub String);
@@ -30,6 +36,18 @@ pub trait BoardParser: fmt::Debug {
     where
         B: Block;
 
+    /// Create the board from the parsed clues.
+    ///
+    /// Fails on the clues unsuitable for a board, e.g. describing too many cells.
+    /// The parsers of this crate implement it, while the `parse` panics on such clues.
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
+    where
+        B: Block,
+    {
+        Ok(self.parse())
+    }
+
+    #[allow(dead_code)]
     fn parse_rc<B>(&self) -> MutRc<Board<B>>
     where
         B: Block,
@@ -37,7 +55,69 @@ pub trait BoardParser: fmt::Debug {
         MutRc::new(self.parse())
     }
 
+    /// Parse the board, checking first that the requested block type
+    /// matches the inferred scheme of the puzzle.
+    fn try_parse<B>(&self) -> Result<Board<B>, ParseError>
+    where
+        B: Block,
+    {
+        let scheme = self.infer_scheme();
+        let expected = PuzzleScheme::of_block::<B>();
+        if scheme != expected {
+            return Err(ParseError(format!(
+                "The puzzle has the {:?} scheme, but the {:?} is requested",
+                scheme, expected
+            )));
+        }
+
+        self.build()
+    }
+
     fn infer_scheme(&self) -> PuzzleScheme;
+
+    /// The number of the block colors in the puzzle (not counting the blank one).
+    ///
+    /// Fails on the same clues the `build` fails on.
+    fn color_count(&self) -> Result<usize, ParseError> {
+        match self.infer_scheme() {
+            PuzzleScheme::BlackAndWhite => Ok(1),
+            PuzzleScheme::MultiColor => {
+                let board = self.build::<ColoredBlock>()?;
+                let colors: HashSet<_> = board
+                    .descriptions(LineDirection::Row)
+                    .iter()
+                    .flat_map(|desc| desc.colors().collect::<Vec<_>>())
+                    .collect();
+                Ok(colors.len())
+            }
+        }
+    }
+}
+
+/// Same as the `Board::with_descriptions_and_palette`,
+/// but fails on the boards with more than `Board::DEFAULT_MAX_AREA` cells.
+fn new_board<B>(
+    rows: Vec<Description<B>>,
+    columns: Vec<Description<B>>,
+    palette: Option<ColorPalette>,
+) -> Result<Board<B>, ParseError>
+where
+    B: Block,
+{
+    Board::try_with_descriptions_and_palette(rows, columns, palette, Board::<B>::DEFAULT_MAX_AREA)
+        .map_err(ParseError)
+}
+
+/// Implement the `BoardParser::parse` panicking on the error of the fallible `build`.
+macro_rules! parse_by_build {
+    () => {
+        fn parse<B>(&self) -> Board<B>
+        where
+            B: Block,
+        {
+            self.build().unwrap_or_else(|err| panic!("{}", err.0))
+        }
+    };
 }
 
 impl From<io::Error> for ParseError {
@@ -46,6 +126,45 @@ impl From<io::Error> for ParseError {
     }
 }
 
+/// The first bytes of any gzip-compressed data
+#[allow(dead_code)]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Decompress the gzipped data, any other data is returned as is
+#[allow(dead_code)]
+fn decompress(raw: Vec<u8>) -> io::Result<Vec<u8>> {
+    if raw.starts_with(&GZIP_MAGIC) {
+        gunzip(&raw)
+    } else {
+        Ok(raw)
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[allow(dead_code)]
+fn gunzip(raw: &[u8]) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut decoded = vec![];
+    let _ = flate2::read::GzDecoder::new(raw).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+#[cfg(not(feature = "gzip"))]
+#[allow(dead_code)]
+fn gunzip(_raw: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Cannot read gzip-compressed content: no support for gzip (hint: add --features=gzip)",
+    ))
+}
+
+#[allow(dead_code)]
+fn decode_utf8(raw: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(raw).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[allow(dead_code)]
 pub trait LocalReader: BoardParser {
     fn read_local(file_name: &str) -> Result<Self, ParseError>
     where
@@ -54,8 +173,10 @@ pub trait LocalReader: BoardParser {
         let content = Self::file_content(file_name)?;
         Self::with_content(&content)
     }
+
+    /// The content of the file, transparently decompressed if it is gzipped
     fn file_content(file_name: &str) -> io::Result<String> {
-        fs::read_to_string(file_name)
+        decode_utf8(decompress(fs::read(file_name)?)?)
     }
 }
 
@@ -75,11 +196,22 @@ pub trait NetworkReader: BoardParser {
         Self::with_content(&content)
     }
 
+    /// The body of the response, decompressed if it has the `Content-Encoding: gzip`
     #[cfg(feature = "web")]
-    fn http_content(url: &str) -> Result<String, reqwest::Error> {
+    fn http_content(url: &str) -> Result<String, ParseError> {
         info!("Requesting {} ...", url);
         let response = reqwest::blocking::get(url)?;
-        response.text()
+        let gzipped = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .map(reqwest::header::HeaderValue::as_bytes)
+            == Some(&b"gzip"[..]);
+
+        if gzipped {
+            let raw = response.bytes()?;
+            return Ok(decode_utf8(gunzip(&raw)?)?);
+        }
+        Ok(response.text()?)
     }
 
     #[cfg(not(feature = "web"))]
@@ -119,13 +251,27 @@ pub enum PuzzleScheme {
     MultiColor,
 }
 
+impl PuzzleScheme {
+    /// The scheme the given block type is able to represent.
+    pub fn of_block<B>() -> Self
+    where
+        B: Block,
+    {
+        if B::Color::blank().as_color_id().is_some() {
+            Self::MultiColor
+        } else {
+            Self::BlackAndWhite
+        }
+    }
+}
+
 #[cfg(feature = "ini")]
 mod ini {
     use serde::Deserialize;
 
     use super::{
-        Block, Board, BoardParser, ColorPalette, Description, LocalReader, Paletted, ParseError,
-        PuzzleScheme,
+        new_board, Block, Board, BoardParser, Color, ColorPalette, Description, LineDirection,
+        LocalReader, Paletted, ParseError, PuzzleScheme,
     };
 
     #[derive(Debug, Deserialize)]
@@ -139,10 +285,19 @@ mod ini {
         defs: Option<Vec<String>>,
     }
 
+    /// The partially solved state of the board: a line of symbols for every row,
+    /// where `.` is blank, `?` is any color and the other symbols
+    /// are the colors from the palette (`X` for the black one).
+    #[derive(Debug, Deserialize)]
+    struct Solution {
+        rows: String,
+    }
+
     #[derive(Debug, Deserialize)]
     pub struct MyFormat {
         clues: Clues,
         colors: Option<Colors>,
+        solution: Option<Solution>,
     }
 
     impl LocalReader for MyFormat {}
@@ -158,17 +313,27 @@ mod ini {
             Ok(toml::from_str(content)?)
         }
 
-        fn parse<B>(&self) -> Board<B>
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
         where
             B: Block,
         {
             let clues = &self.clues;
             let palette = self.get_palette();
-            Board::with_descriptions_and_palette(
+            let mut board = new_board(
                 Self::parse_clues(&clues.rows, &palette),
                 Self::parse_clues(&clues.columns, &palette),
-                Some(palette),
-            )
+                Some(palette.clone()),
+            )?;
+
+            if let Some(solution) = &self.solution {
+                let cells = Self::parse_solution(&solution.rows, &palette, &board)?;
+                board
+                    .overlay(&cells)
+                    .map_err(|err| ParseError(format!("Bad solution: {}", err)))?;
+            }
+            Ok(board)
         }
 
         fn infer_scheme(&self) -> PuzzleScheme {
@@ -190,7 +355,7 @@ mod ini {
             B: Block,
         {
             let mut as_chars = block.chars();
-            let value_color_pos = as_chars.position(|c| !c.is_digit(10));
+            let value_color_pos = as_chars.position(|c| !c.is_ascii_digit());
             #[allow(clippy::option_if_let_else)]
             let (value, block_color) = if let Some(pos) = value_color_pos {
                 let (value, color) = block.split_at(pos);
@@ -249,6 +414,60 @@ mod ini {
                 .collect()
         }
 
+        /// The cells given by the `[solution]` section.
+        /// The unknown (`?`) cells are left as they are on the fresh board.
+        fn parse_solution<B>(
+            rows: &str,
+            palette: &ColorPalette,
+            board: &Board<B>,
+        ) -> Result<Vec<B::Color>, ParseError>
+        where
+            B: Block,
+        {
+            let rows: Vec<Vec<char>> = rows
+                .lines()
+                .map(|row| row.chars().filter(|c| !c.is_whitespace()).collect())
+                .filter(|row: &Vec<_>| !row.is_empty())
+                .collect();
+
+            if rows.len() != board.height() {
+                return Err(ParseError(format!(
+                    "The solution should have a line for every row: expected {}, found {}",
+                    board.height(),
+                    rows.len()
+                )));
+            }
+
+            let initial = board.make_snapshot();
+            let mut cells = Vec::with_capacity(initial.len());
+            for (y, row) in rows.iter().enumerate() {
+                if row.len() != board.width() {
+                    return Err(ParseError(format!(
+                        "The solution row {} should have a symbol for every column: expected {}, found {}",
+                        y,
+                        board.width(),
+                        row.len()
+                    )));
+                }
+
+                for (x, &symbol) in row.iter().enumerate() {
+                    let cell = match symbol {
+                        '?' => initial[y * board.width() + x],
+                        '.' => B::Color::blank(),
+                        _ => {
+                            let id = palette.id_by_symbol(symbol).ok_or_else(|| {
+                                ParseError(format!("Unknown symbol {:?} in the solution", symbol))
+                            })?;
+                            B::Color::from_color_ids(&[id])
+                        }
+                    };
+                    cells.push(cell);
+                }
+            }
+
+            Ok(cells)
+        }
+
         ///```
         /// # use nonogrid::parser::MyFormat;
         ///
@@ -265,6 +484,87 @@ mod ini {
             desc = desc.trim().trim_matches(&['(', ')'][..]).to_string();
             (name.to_string(), symbol, desc)
         }
+
+        /// The clues of the board in the format readable by the `MyFormat::with_content`
+        /// (the cells are not exported): the `[clues]` section with the rows and the columns
+        /// and the `[colors]` section with the `name = (r, g, b) symbol` definitions.
+        ///
+        /// The blocks of the default black color are written without the color name,
+        /// the others get the palette name after the size (e.g. `2r`).
+        #[allow(dead_code)]
+        pub fn to_toml<B>(board: &Board<B>) -> String
+        where
+            B: Block,
+        {
+            let multicolor = PuzzleScheme::of_block::<B>() == PuzzleScheme::MultiColor;
+            let palette = board.palette().filter(|_| multicolor);
+            let default_id = palette
+                .and_then(|palette| {
+                    palette
+                        .get_default()
+                        .and_then(|name| palette.id_by_name(name))
+                })
+                .filter(|&id| board.desc_by_id(id).map(|desc| desc.rgb_value()) == Some((0, 0, 0)));
+
+            let block = |block: &B| {
+                let name = block
+                    .color()
+                    .as_color_id()
+                    .filter(|&id| multicolor && Some(id) != default_id)
+                    .and_then(|id| board.desc_by_id(id));
+                match name {
+                    Some(desc) => format!("{}{}", block.size(), desc.palette_name()),
+                    None => block.size().to_string(),
+                }
+            };
+            let clues = |direction| {
+                board
+                    .descriptions(direction)
+                    .iter()
+                    .map(|desc| {
+                        if desc.vec.is_empty() {
+                            return "0".to_string();
+                        }
+                        desc.vec.iter().map(block).collect::<Vec<_>>().join(" ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            let mut lines = vec![
+                "[clues]".to_string(),
+                format!("rows = \"{}\"", clues(LineDirection::Row)),
+                format!("columns = \"{}\"", clues(LineDirection::Column)),
+            ];
+
+            if let Some(palette) = palette {
+                let defs: Vec<_> = palette
+                    .iter()
+                    .filter(|desc| {
+                        desc.id() != ColorPalette::WHITE_ID && Some(desc.id()) != default_id
+                    })
+                    .map(|desc| {
+                        let (r, g, b) = desc.rgb_value();
+                        format!(
+                            "\"{} = ({}, {}, {}) {}\"",
+                            desc.palette_name(),
+                            r,
+                            g,
+                            b,
+                            desc.symbol()
+                        )
+                    })
+                    .collect();
+
+                if !defs.is_empty() {
+                    lines.push(String::new());
+                    lines.push("[colors]".to_string());
+                    lines.push(format!("defs = [{}]", defs.join(", ")));
+                }
+            }
+
+            lines.join("\n")
+        }
     }
 
     impl Paletted for MyFormat {
@@ -305,12 +605,180 @@ mod ini {
             Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
         }
 
-        fn parse<B>(&self) -> Board<B>
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
         where
             B: Block,
         {
+            Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
+        }
+
+        fn infer_scheme(&self) -> PuzzleScheme {
             unimplemented!("{}", Self::NO_FEATURE_ENABLED_MSG)
         }
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::{
+        new_board, Block, Board, BoardParser, ColorPalette, ColorValue, Description, LocalReader,
+        Paletted, ParseError, PuzzleScheme,
+    };
+
+    #[derive(Debug, Deserialize)]
+    #[serde(untagged)]
+    enum JsonBlock {
+        /// The block of the default (black) color
+        Size(usize),
+        Colored {
+            size: usize,
+            color: String,
+        },
+    }
+
+    /// The puzzle given as the JSON object with the `rows` and `columns`
+    /// (the array of blocks for every line) and the optional `colors`
+    /// mapping the color names into their values (e.g. `"#f00"` or `"red"`):
+    ///
+    /// ```json
+    /// {"rows": [[1, {"size": 2, "color": "r"}], []], "columns": [...], "colors": {"r": "red"}}
+    /// ```
+    #[derive(Debug, Deserialize)]
+    pub struct JsonParser {
+        rows: Vec<Vec<JsonBlock>>,
+        columns: Vec<Vec<JsonBlock>>,
+        #[serde(default)]
+        colors: BTreeMap<String, String>,
+    }
+
+    impl LocalReader for JsonParser {}
+
+    impl From<serde_json::Error> for ParseError {
+        fn from(err: serde_json::Error) -> Self {
+            Self(format!("{:?}", err))
+        }
+    }
+
+    impl BoardParser for JsonParser {
+        fn with_content(content: &str) -> Result<Self, ParseError> {
+            let this: Self = serde_json::from_str(content)?;
+
+            let palette = this.get_palette();
+            let unknown_color = this
+                .rows
+                .iter()
+                .chain(&this.columns)
+                .flatten()
+                .find_map(|block| match block {
+                    JsonBlock::Colored { color, .. } if !palette.contains(color) => Some(color),
+                    _ => None,
+                });
+            if let Some(color) = unknown_color {
+                return Err(ParseError(format!("Unknown block color {:?}", color)));
+            }
+
+            Ok(this)
+        }
+
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
+        where
+            B: Block,
+        {
+            let palette = self.get_palette();
+            new_board(
+                Self::parse_clues(&self.rows, &palette),
+                Self::parse_clues(&self.columns, &palette),
+                Some(palette),
+            )
+        }
+
+        fn infer_scheme(&self) -> PuzzleScheme {
+            if self.colors.is_empty() {
+                PuzzleScheme::BlackAndWhite
+            } else {
+                PuzzleScheme::MultiColor
+            }
+        }
+    }
+
+    impl JsonParser {
+        fn parse_clues<B>(lines: &[Vec<JsonBlock>], palette: &ColorPalette) -> Vec<Description<B>>
+        where
+            B: Block,
+        {
+            lines
+                .iter()
+                .map(|line| {
+                    Description::new(
+                        line.iter()
+                            .map(|block| {
+                                let (size, color) = match block {
+                                    JsonBlock::Size(size) => (*size, palette.get_default()),
+                                    JsonBlock::Colored { size, color } => (*size, Some(&**color)),
+                                };
+                                let color_id = color.and_then(|name| palette.id_by_name(name));
+                                B::from_size_and_color(size, color_id)
+                            })
+                            .collect(),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    impl Paletted for JsonParser {
+        /// The symbols are not given in JSON, so they are chosen by the palette.
+        fn get_colors(&self) -> Vec<(String, char, String)> {
+            vec![]
+        }
+
+        fn get_palette(&self) -> ColorPalette {
+            let mut palette = ColorPalette::with_white_and_black("white", "black");
+            for (name, value) in &self.colors {
+                palette.color_with_name_and_value(name, ColorValue::parse(value));
+            }
+            palette
+        }
+    }
+}
+
+#[cfg(not(feature = "json"))]
+mod json {
+    //! dummy definitions
+    use super::{Block, Board, BoardParser, ParseError, PuzzleScheme};
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct JsonParser;
+
+    impl JsonParser {
+        const NO_FEATURE_ENABLED_MSG: &'static str =
+            "Cannot parse JSON puzzles: no support for JSON (hint: add --features=json)";
+    }
+
+    impl BoardParser for JsonParser {
+        fn with_content(_content: &str) -> Result<Self, ParseError>
+        where
+            Self: Sized,
+        {
+            Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
+        }
+
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
+        where
+            B: Block,
+        {
+            Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
+        }
 
         fn infer_scheme(&self) -> PuzzleScheme {
             unimplemented!("{}", Self::NO_FEATURE_ENABLED_MSG)
@@ -327,11 +795,14 @@ mod xml {
         Value,
     };
 
-    use crate::utils::rc::{mutate_ref, read_ref, InteriorMutableRef};
+    use crate::{
+        block::base::color::ColorDesc,
+        utils::rc::{mutate_ref, read_ref, InteriorMutableRef},
+    };
 
     use super::{
-        Block, Board, BoardParser, ColorPalette, Description, LocalReader, NetworkReader, Paletted,
-        ParseError, PuzzleScheme,
+        new_board, Block, Board, BoardParser, Color, ColorId, ColorPalette, Description,
+        LineDirection, LocalReader, NetworkReader, Paletted, ParseError, PuzzleScheme,
     };
 
     #[derive(Debug)]
@@ -369,11 +840,13 @@ mod xml {
             })
         }
 
-        fn parse<B>(&self) -> Board<B>
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
         where
             B: Block,
         {
-            Board::with_descriptions_and_palette(
+            new_board(
                 self.parse_clues("rows"),
                 self.parse_clues("columns"),
                 Some(self.get_palette()),
@@ -514,6 +987,111 @@ mod xml {
         }
     }
 
+    #[allow(dead_code)]
+    impl WebPbn {
+        /// The puzzle in the webpbn XML format: the colors from the palette
+        /// and the clues of the board (the cells are not exported).
+        /// The parsed result has the same clues as the board
+        /// (up to the IDs of the colors, which are assigned by the parser).
+        ///
+        /// The blank color is always named `white` and the black one `black`,
+        /// since the parser always defines them with these names.
+        pub fn export<B>(board: &Board<B>) -> String
+        where
+            B: Block,
+        {
+            let multicolor = PuzzleScheme::of_block::<B>() == PuzzleScheme::MultiColor;
+            let color_name = |id: ColorId| -> Option<String> {
+                let desc = board.desc_by_id(id);
+                if id == ColorPalette::WHITE_ID {
+                    Some("white".to_string())
+                } else if desc.as_ref().map(ColorDesc::rgb_value) == Some((0, 0, 0)) {
+                    Some("black".to_string())
+                } else {
+                    desc.map(|desc| desc.name().to_string())
+                }
+            };
+
+            let palette = board.palette().filter(|_| multicolor);
+            let default_color = palette.map_or(Some("black".to_string()), |palette| {
+                palette
+                    .get_default()
+                    .and_then(|name| palette.id_by_name(name))
+                    .and_then(color_name)
+            });
+            let puzzle = match default_color {
+                Some(name) => format!(r#"<puzzle type="grid" defaultcolor="{}">"#, escape(&name)),
+                None => r#"<puzzle type="grid">"#.to_string(),
+            };
+
+            let mut lines = vec![
+                r#"<?xml version="1.0"?>"#.to_string(),
+                r#"<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">"#.to_string(),
+                "<puzzleset>".to_string(),
+                puzzle,
+            ];
+
+            match palette {
+                Some(palette) => lines.extend(palette.iter().filter_map(|desc| {
+                    let name = color_name(desc.id())?;
+                    Some(format!(
+                        r#"<color name="{}" char="{}">{}</color>"#,
+                        escape(&name),
+                        escape(&desc.symbol()),
+                        desc.hex().trim_start_matches('#')
+                    ))
+                })),
+                None => {
+                    lines.push(r#"<color name="white" char=".">fff</color>"#.to_string());
+                    lines.push(r#"<color name="black" char="X">000</color>"#.to_string());
+                }
+            }
+
+            for (direction, type_) in &[
+                (LineDirection::Column, "columns"),
+                (LineDirection::Row, "rows"),
+            ] {
+                lines.push(format!(r#"<clues type="{}">"#, type_));
+                for desc in board.descriptions(*direction) {
+                    let blocks: String = desc
+                        .vec
+                        .iter()
+                        .map(|block| {
+                            let name = block
+                                .color()
+                                .as_color_id()
+                                .filter(|_| multicolor)
+                                .and_then(color_name);
+                            match name {
+                                Some(name) => format!(
+                                    r#"<count color="{}">{}</count>"#,
+                                    escape(&name),
+                                    block.size()
+                                ),
+                                None => format!("<count>{}</count>", block.size()),
+                            }
+                        })
+                        .collect();
+                    lines.push(format!("<line>{}</line>", blocks));
+                }
+                lines.push("</clues>".to_string());
+            }
+
+            lines.push("</puzzle>".to_string());
+            lines.push("</puzzleset>".to_string());
+            lines.join("\n")
+        }
+    }
+
+    #[allow(dead_code)]
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
     impl Paletted for WebPbn {
         fn get_colors(&self) -> Vec<(String, char, String)> {
             if let Some(colors) = read_ref(&self.cached_colors).as_ref() {
@@ -560,11 +1138,13 @@ mod xml {
             Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
         }
 
-        fn parse<B>(&self) -> Board<B>
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
         where
             B: Block,
         {
-            unimplemented!("{}", Self::NO_FEATURE_ENABLED_MSG)
+            Err(ParseError(Self::NO_FEATURE_ENABLED_MSG.to_string()))
         }
 
         fn infer_scheme(&self) -> PuzzleScheme {
@@ -575,39 +1155,142 @@ mod xml {
     impl NetworkReader for WebPbn {}
 }
 
-type EncodedInt = u16;
-
-#[derive(Debug)]
-pub struct NonogramsOrg {
-    encoded: Vec<Vec<EncodedInt>>,
-}
+#[cfg(feature = "png")]
+mod png {
+    use std::path::Path;
 
-impl NonogramsOrg {
-    const URLS: [&'static str; 2] = ["http://www.nonograms.ru/", "http://www.nonograms.org/"];
-    const PATHS: [(PuzzleScheme, &'static str); 2] = [
-        (PuzzleScheme::BlackAndWhite, "nonograms"),
-        (PuzzleScheme::MultiColor, "nonograms2"),
-    ];
-    const CYPHER_PREFIX: &'static str = r"var d=";
-    const CYPHER_SUFFIX: char = ';';
+    use image::{DynamicImage, GenericImageView, ImageError, Pixel};
 
-    fn extract_encoded_json(html: &str) -> Option<&str> {
-        #[allow(unused_imports)]
-        use crate::utils::Stripper; // for Rust<1.45
+    use super::{
+        clues_from_solution, new_board, Block, Board, BoardParser, ColorId, ColorPalette, Paletted,
+        ParseError, PuzzleScheme,
+    };
 
-        html.lines().find_map(|line| {
-            line.strip_prefix(Self::CYPHER_PREFIX)
-                .map(|line| line.trim_end_matches(Self::CYPHER_SUFFIX))
-        })
+    /// Black-and-white puzzle made from the raster image:
+    /// every pixel becomes a cell, the dark pixels are black, others are white.
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    pub struct ImageParser {
+        solution_matrix: Vec<Vec<ColorId>>,
     }
 
-    fn parse_line(line: &str) -> Vec<EncodedInt> {
-        line.split(',')
-            .map(|x| x.parse().expect("The items should be positive integers"))
+    impl From<ImageError> for ParseError {
+        fn from(err: ImageError) -> Self {
+            Self(format!("{:?}", err))
+        }
+    }
+
+    #[allow(dead_code)]
+    impl ImageParser {
+        /// The pixels with the brightness lower than that are considered black.
+        pub const DEFAULT_THRESHOLD: u8 = 128;
+
+        const WHITE: ColorId = ColorPalette::WHITE_ID;
+        // the ID of black color in the `ColorPalette::with_white_and_black`
+        const BLACK: ColorId = ColorPalette::WHITE_ID * 2;
+
+        pub fn from_image(image: &DynamicImage, threshold: u8) -> Self {
+            let (width, height) = image.dimensions();
+            let solution_matrix = (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| {
+                            let pixel = image.get_pixel(x, y);
+                            let alpha = pixel.to_rgba()[3];
+                            let brightness = pixel.to_luma()[0];
+                            // (semi)transparent pixels are the background
+                            if alpha > u8::MAX / 2 && brightness < threshold {
+                                Self::BLACK
+                            } else {
+                                Self::WHITE
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Self { solution_matrix }
+        }
+
+        pub fn read_image<P: AsRef<Path>>(path: P, threshold: u8) -> Result<Self, ParseError> {
+            let image = image::open(path)?;
+            Ok(Self::from_image(&image, threshold))
+        }
+    }
+
+    impl BoardParser for ImageParser {
+        fn with_content(_content: &str) -> Result<Self, ParseError> {
+            Err(ParseError(
+                "Cannot parse an image from text: use `ImageParser::read_image`".to_string(),
+            ))
+        }
+
+        parse_by_build!();
+
+        fn build<B>(&self) -> Result<Board<B>, ParseError>
+        where
+            B: Block,
+        {
+            let (columns, rows) = clues_from_solution(&self.solution_matrix, Self::WHITE);
+            new_board(rows, columns, Some(self.get_palette()))
+        }
+
+        fn infer_scheme(&self) -> PuzzleScheme {
+            PuzzleScheme::BlackAndWhite
+        }
+    }
+
+    impl Paletted for ImageParser {
+        fn get_colors(&self) -> Vec<(String, char, String)> {
+            vec![]
+        }
+
+        fn get_palette(&self) -> ColorPalette {
+            self.default_palette("white", "black")
+        }
+    }
+}
+
+type EncodedInt = u16;
+
+#[derive(Debug)]
+pub struct NonogramsOrg {
+    encoded: Vec<Vec<EncodedInt>>,
+}
+
+impl NonogramsOrg {
+    const URLS: [&'static str; 2] = ["http://www.nonograms.ru/", "http://www.nonograms.org/"];
+    const PATHS: [(PuzzleScheme, &'static str); 2] = [
+        (PuzzleScheme::BlackAndWhite, "nonograms"),
+        (PuzzleScheme::MultiColor, "nonograms2"),
+    ];
+    const CYPHER_PREFIX: &'static str = r"var d=";
+    const CYPHER_SUFFIX: char = ';';
+
+    fn extract_encoded_json(html: &str) -> Option<&str> {
+        #[allow(unused_imports)]
+        use crate::utils::Stripper; // for Rust<1.45
+
+        html.lines().find_map(|line| {
+            line.strip_prefix(Self::CYPHER_PREFIX)
+                .map(|line| line.trim_end_matches(Self::CYPHER_SUFFIX))
+        })
+    }
+
+    fn parse_line(line: &str) -> Result<Vec<EncodedInt>, ParseError> {
+        line.split(',')
+            .map(|x| {
+                x.parse().map_err(|_| {
+                    ParseError(format!(
+                        "The cypher items should be positive integers: {:?}",
+                        x
+                    ))
+                })
+            })
             .collect()
     }
 
-    fn parse_json(array: &str) -> Vec<Vec<EncodedInt>> {
+    fn parse_json(array: &str) -> Result<Vec<Vec<EncodedInt>>, ParseError> {
         array
             .trim_start_matches('[')
             .trim_end_matches(']')
@@ -616,69 +1299,129 @@ impl NonogramsOrg {
             .collect()
     }
 
+    fn malformed(reason: &str) -> ParseError {
+        ParseError(format!("Malformed cypher: {}", reason))
+    }
+
+    /// Every item of the cypher used by the `decipher` is an array of (at least) 4 numbers.
+    fn cypher_item(&self, index: usize) -> Result<&[EncodedInt], ParseError> {
+        let item = self
+            .encoded
+            .get(index)
+            .ok_or_else(|| Self::malformed(&format!("the item {} is missing", index)))?;
+
+        if item.len() < 4 {
+            return Err(Self::malformed(&format!("the item {} is too short", index)));
+        }
+        Ok(item)
+    }
+
+    fn sub(a: EncodedInt, b: EncodedInt) -> Result<EncodedInt, ParseError> {
+        a.checked_sub(b)
+            .ok_or_else(|| Self::malformed("unexpected negative value"))
+    }
+
+    /// The remainders of the first three numbers of the item divided by the fourth one.
+    ///
+    /// They are widened to the `usize`, so the further arithmetic does not overflow.
+    fn remainders(x: &[EncodedInt]) -> Result<[usize; 3], ParseError> {
+        let divisor = usize::from(x[3]);
+        if divisor == 0 {
+            return Err(Self::malformed("zero divisor"));
+        }
+        Ok([
+            usize::from(x[0]) % divisor,
+            usize::from(x[1]) % divisor,
+            usize::from(x[2]) % divisor,
+        ])
+    }
+
+    fn decipher_number(x: &[EncodedInt]) -> Result<usize, ParseError> {
+        let [a, b, c] = Self::remainders(x)?;
+        (a + b)
+            .checked_sub(c)
+            .ok_or_else(|| Self::malformed("unexpected negative value"))
+    }
+
     /// Reverse engineered version of the part of the script
     /// <http://www.nonograms.org/js/nonogram.min.059.js>
     /// that produces a nonogram solution for the given cyphered solution
     /// (it can be found in puzzle HTML in the form 'var d=[...]').
+    ///
+    /// Fails if the cypher is truncated or malformed.
     #[allow(clippy::shadow_unrelated)]
     #[allow(unknown_lints)]
     #[allow(clippy::no_effect_underscore_binding)]
-    pub fn decipher(&self) -> (Vec<String>, Vec<Vec<ColorId>>) {
-        let cyphered = self.encoded();
-
-        let x = &cyphered[1];
-        let width = (x[0] % x[3] + x[1] % x[3] - x[2] % x[3]) as usize;
-
-        let x = &cyphered[2];
-        let height = (x[0] % x[3] + x[1] % x[3] - x[2] % x[3]) as usize;
-
-        let x = &cyphered[3];
-        let colors_number = (x[0] % x[3] + x[1] % x[3] - x[2] % x[3]) as usize;
+    pub fn decipher(&self) -> Result<(Vec<String>, Vec<Vec<ColorId>>), ParseError> {
+        let width = Self::decipher_number(self.cypher_item(1)?)?;
+        let height = Self::decipher_number(self.cypher_item(2)?)?;
+        let colors_number = Self::decipher_number(self.cypher_item(3)?)?;
+        let area = width
+            .checked_mul(height)
+            .filter(|&area| area <= Board::<BinaryBlock>::DEFAULT_MAX_AREA);
+        if area.is_none() {
+            return Err(Self::malformed("the board is too big"));
+        }
 
-        let x = &cyphered[4];
-        let colors: Vec<_> = (0..colors_number)
+        let x = self.cypher_item(4)?;
+        let colors = (0..colors_number)
             .map(|c| {
-                let color_x = &cyphered[c + 5];
-                let a = color_x[0] - x[1];
-                let b = u32::from(color_x[1] - x[0]);
-                let c = u32::from(color_x[2] - x[3]);
-                let _unknown_flag = color_x[3] - a - x[2];
-                let a = &format!("{:x}", a + 256)[1..];
+                let color_x = self.cypher_item(c + 5)?;
+                let a = Self::sub(color_x[0], x[1])?;
+                let b = u32::from(Self::sub(color_x[1], x[0])?);
+                let c = u32::from(Self::sub(color_x[2], x[3])?);
+                let _unknown_flag = color_x[3].wrapping_sub(a).wrapping_sub(x[2]);
+                let a = &format!("{:x}", u32::from(a) + 256)[1..];
                 let b = &format!("{:x}", ((b + 256) << 8) + c)[1..];
-                a.to_string() + b
+                Ok(a.to_string() + b)
             })
-            .collect();
+            .collect::<Result<Vec<_>, ParseError>>()?;
 
         let mut solution = vec![vec![0; width]; height];
         let z = colors_number + 5;
-        let x = &cyphered[z];
-        let solution_size = (x[0] % x[3] * (x[0] % x[3]) + x[1] % x[3] * 2 + x[2] % x[3]) as usize;
-
-        let x = &cyphered[z + 1];
+        let [a, b, c] = Self::remainders(self.cypher_item(z)?)?;
+        let solution_size = a
+            .checked_mul(a)
+            .and_then(|size| size.checked_add(b * 2))
+            .and_then(|size| size.checked_add(c))
+            .ok_or_else(|| Self::malformed("the solution is too big"))?;
+
+        let x = self.cypher_item(z + 1)?;
         for i in 0..solution_size {
-            let y = &cyphered[z + 2 + i];
-            let vv = y[0] - x[0] - 1;
+            let y = self.cypher_item(z + 2 + i)?;
+            let vv = usize::from(Self::sub(Self::sub(y[0], x[0])?, 1)?);
+            let block_size = usize::from(Self::sub(y[1], x[1])?);
+            if block_size == 0 {
+                continue;
+            }
 
-            for j in 0..(y[1] - x[1]) {
-                let v = (j + vv) as usize;
-                let xx = y[3] - x[3] - 1;
-                solution[xx as usize][v] = ColorId::from(y[2] - x[2]);
+            let xx = Self::sub(Self::sub(y[3], x[3])?, 1)?;
+            let color = ColorId::from(Self::sub(y[2], x[2])?);
+            let row = solution
+                .get_mut(usize::from(xx))
+                .ok_or_else(|| Self::malformed("the row is out of the board"))?;
+            for j in 0..block_size {
+                let cell = row
+                    .get_mut(j + vv)
+                    .ok_or_else(|| Self::malformed("the column is out of the board"))?;
+                *cell = color;
             }
         }
 
-        (colors, solution)
+        Ok((colors, solution))
     }
 
+    #[allow(dead_code)]
     pub fn encoded(&self) -> &[Vec<EncodedInt>] {
         &self.encoded
     }
 
-    fn get_solution_matrix(&self) -> Vec<Vec<ColorId>> {
-        let (_colors, solution_matrix) = self.decipher();
+    fn get_solution_matrix(&self) -> Result<Vec<Vec<ColorId>>, ParseError> {
+        let (_colors, solution_matrix) = self.decipher()?;
         let palette = self.get_palette();
 
         let mut mapping_cache = HashMap::new();
-        solution_matrix
+        Ok(solution_matrix
             .iter()
             .map(|row| {
                 row.iter()
@@ -691,7 +1434,13 @@ impl NonogramsOrg {
                     })
                     .collect()
             })
-            .collect()
+            .collect())
+    }
+
+    /// The cypher is checked to be valid when the parser is created.
+    fn deciphered(&self) -> (Vec<String>, Vec<Vec<ColorId>>) {
+        self.decipher()
+            .expect("The cypher should be checked on creation")
     }
 
     fn color_name_by_id(id: ColorId) -> String {
@@ -724,23 +1473,28 @@ impl BoardParser for NonogramsOrg {
         let json = Self::extract_encoded_json(content)
             .ok_or_else(|| ParseError("Not found cypher in HTML content".to_string()))?;
 
-        Ok(Self {
-            encoded: Self::parse_json(json),
-        })
+        let this = Self {
+            encoded: Self::parse_json(json)?,
+        };
+        // fail early to allow the caller to try another source
+        let _ = this.decipher()?;
+        Ok(this)
     }
 
-    fn parse<B>(&self) -> Board<B>
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
     where
         B: Block,
     {
-        let solution_matrix = self.get_solution_matrix();
+        let solution_matrix = self.get_solution_matrix()?;
         let (columns, rows) = clues_from_solution(&solution_matrix, 0);
 
-        Board::with_descriptions_and_palette(rows, columns, Some(self.get_palette()))
+        new_board(rows, columns, Some(self.get_palette()))
     }
 
     fn infer_scheme(&self) -> PuzzleScheme {
-        let (colors, _solution) = self.decipher();
+        let (colors, _solution) = self.deciphered();
         if colors.len() == 1 {
             assert_eq!(colors, ["000000"]);
             return PuzzleScheme::BlackAndWhite;
@@ -748,12 +1502,17 @@ impl BoardParser for NonogramsOrg {
 
         PuzzleScheme::MultiColor
     }
+
+    fn color_count(&self) -> Result<usize, ParseError> {
+        let (colors, _solution) = self.deciphered();
+        Ok(colors.len())
+    }
 }
 
 impl Paletted for NonogramsOrg {
     #[allow(clippy::cast_possible_truncation)]
     fn get_colors(&self) -> Vec<(String, char, String)> {
-        let (colors, _solution) = self.decipher();
+        let (colors, _solution) = self.deciphered();
         colors
             .into_iter()
             .enumerate()
@@ -774,15 +1533,76 @@ impl Paletted for NonogramsOrg {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum ParserKind {
     Toml,
+    Json,
     WebPbn,
     NonogramsOrg,
     Olsak,
+    Non,
+    AsciiArt,
     Simple,
 }
 
+impl ParserKind {
+    /// The order to try the parsers when the detected one fails
+    const ALL: [Self; 8] = [
+        Self::Toml,
+        Self::Json,
+        Self::WebPbn,
+        Self::NonogramsOrg,
+        Self::Olsak,
+        Self::Non,
+        Self::AsciiArt,
+        Self::Simple,
+    ];
+
+    /// The parser for the well-known file extension
+    #[allow(dead_code)]
+    fn by_extension(file_name: &str) -> Option<Self> {
+        let extension = Path::new(file_name).extension()?.to_str()?;
+        match extension.to_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "xml" => Some(Self::WebPbn),
+            "non" => Some(Self::Non),
+            "g" => Some(Self::Olsak),
+            _ => None,
+        }
+    }
+
+    fn detect(content: &str) -> Self {
+        let trim_content = content.trim();
+        if trim_content.starts_with("<?xml") {
+            return Self::WebPbn;
+        }
+
+        if trim_content.starts_with('{') {
+            return Self::Json;
+        }
+
+        if ["<!DOCTYPE HTML", "<html", NonogramsOrg::CYPHER_PREFIX]
+            .iter()
+            .any(|&prefix| trim_content.starts_with(prefix))
+        {
+            return Self::NonogramsOrg;
+        }
+
+        let lines: Vec<_> = trim_content.lines().map(str::trim).collect();
+        if lines.contains(&"[clues]") {
+            Self::Toml
+        } else if lines.contains(&": rows") {
+            Self::Olsak
+        } else if NonParser::looks_like_non(&lines) {
+            Self::Non
+        } else if AsciiArtParser::looks_like_art(trim_content) {
+            Self::AsciiArt
+        } else {
+            Self::Simple
+        }
+    }
+}
+
 pub struct DetectedParser {
     parser_kind: ParserKind,
     inner: Box<dyn Any>,
@@ -798,75 +1618,146 @@ impl DetectedParser {
     }
 }
 
-impl BoardParser for DetectedParser {
-    fn with_content(content: &str) -> Result<Self, ParseError> {
-        let trim_content = content.trim();
-        Ok(if trim_content.starts_with("<?xml") {
-            Self {
-                parser_kind: ParserKind::WebPbn,
-                inner: Box::new(WebPbn::with_content(content)?),
-            }
-        } else if ["<!DOCTYPE HTML", "<html", NonogramsOrg::CYPHER_PREFIX]
-            .iter()
-            .any(|&prefix| trim_content.starts_with(prefix))
-        {
-            Self {
-                parser_kind: ParserKind::NonogramsOrg,
-                inner: Box::new(NonogramsOrg::with_content(content)?),
-            }
-        } else {
-            let lines: Vec<_> = trim_content.lines().map(str::trim).collect();
-            if lines.contains(&"[clues]") {
-                Self {
-                    parser_kind: ParserKind::Toml,
-                    inner: Box::new(MyFormat::with_content(content)?),
-                }
-            } else if lines.contains(&": rows") {
-                Self {
-                    parser_kind: ParserKind::Olsak,
-                    inner: Box::new(OlsakParser::with_content(content)?),
-                }
-            } else {
-                Self {
-                    parser_kind: ParserKind::Simple,
-                    inner: Box::new(SimpleParser::with_content(content)?),
+impl DetectedParser {
+    fn with_kind(parser_kind: ParserKind, content: &str) -> Result<Self, ParseError> {
+        let inner: Box<dyn Any> = match parser_kind {
+            ParserKind::Toml => Box::new(MyFormat::with_content(content)?),
+            ParserKind::Json => Box::new(JsonParser::with_content(content)?),
+            ParserKind::WebPbn => Box::new(WebPbn::with_content(content)?),
+            ParserKind::NonogramsOrg => Box::new(NonogramsOrg::with_content(content)?),
+            ParserKind::Olsak => Box::new(OlsakParser::with_content(content)?),
+            ParserKind::Non => Box::new(NonParser::with_content(content)?),
+            ParserKind::AsciiArt => Box::new(AsciiArtParser::with_content(content)?),
+            ParserKind::Simple => Box::new(SimpleParser::with_content(content)?),
+        };
+
+        Ok(Self { parser_kind, inner })
+    }
+
+    /// Try the detected parser first and then all the others in turn
+    /// until any of them succeeds.
+    ///
+    /// If all the parsers fail, the error lists the complaints of every one of them.
+    pub fn with_content_fallback(content: &str) -> Result<Self, ParseError> {
+        let detected = ParserKind::detect(content);
+        let kinds = once(detected).chain(
+            ParserKind::ALL
+                .iter()
+                .copied()
+                .filter(move |&kind| kind != detected),
+        );
+
+        let mut errors = vec![];
+        for kind in kinds {
+            match Self::with_kind(kind, content) {
+                Ok(parser) => {
+                    if !errors.is_empty() {
+                        warn!(
+                            "Parsed with the {:?} parser after the failures: {:?}",
+                            kind, errors
+                        );
+                    }
+                    return Ok(parser);
                 }
+                Err(err) => errors.push(format!("{:?}: {}", kind, err.0)),
             }
-        })
+        }
+
+        Err(ParseError(format!(
+            "None of the parsers succeeded:\n{}",
+            errors.join("\n")
+        )))
+    }
+}
+
+#[allow(dead_code)]
+impl DetectedParser {
+    /// Read the file choosing the parser by its extension
+    /// (`.toml`, `.xml`, `.non` or `.g`).
+    /// The content is sniffed as usual if the extension is unknown.
+    pub fn read_local_with_hint(path: &str) -> Result<Self, ParseError> {
+        let content = Self::file_content(path)?;
+        let parser_kind =
+            ParserKind::by_extension(path).unwrap_or_else(|| ParserKind::detect(&content));
+        Self::with_kind(parser_kind, &content)
+    }
+}
+
+impl LocalReader for DetectedParser {
+    fn read_local(file_name: &str) -> Result<Self, ParseError> {
+        Self::read_local_with_hint(file_name)
+    }
+}
+
+impl BoardParser for DetectedParser {
+    fn with_content(content: &str) -> Result<Self, ParseError> {
+        Self::with_kind(ParserKind::detect(content), content)
     }
 
     //noinspection RsTypeCheck
-    fn parse<B>(&self) -> Board<B>
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
     where
         B: Block,
     {
+        let scheme = self.infer_scheme();
+        if scheme != PuzzleScheme::of_block::<B>() {
+            warn!(
+                "Parsing the {:?} puzzle with the unsuitable block type (hint: use `try_parse`)",
+                scheme
+            );
+        }
+
         match self.parser_kind {
-            ParserKind::Toml => self.cast::<MyFormat>().parse::<B>(),
-            ParserKind::WebPbn => self.cast::<WebPbn>().parse::<B>(),
-            ParserKind::NonogramsOrg => self.cast::<NonogramsOrg>().parse::<B>(),
-            ParserKind::Olsak => self.cast::<OlsakParser>().parse::<B>(),
-            ParserKind::Simple => self.cast::<SimpleParser>().parse::<B>(),
+            ParserKind::Toml => self.cast::<MyFormat>().build::<B>(),
+            ParserKind::Json => self.cast::<JsonParser>().build::<B>(),
+            ParserKind::WebPbn => self.cast::<WebPbn>().build::<B>(),
+            ParserKind::NonogramsOrg => self.cast::<NonogramsOrg>().build::<B>(),
+            ParserKind::Olsak => self.cast::<OlsakParser>().build::<B>(),
+            ParserKind::Non => self.cast::<NonParser>().build::<B>(),
+            ParserKind::AsciiArt => self.cast::<AsciiArtParser>().build::<B>(),
+            ParserKind::Simple => self.cast::<SimpleParser>().build::<B>(),
         }
     }
 
     fn infer_scheme(&self) -> PuzzleScheme {
         match self.parser_kind {
             ParserKind::Toml => self.cast::<MyFormat>().infer_scheme(),
+            ParserKind::Json => self.cast::<JsonParser>().infer_scheme(),
             ParserKind::WebPbn => self.cast::<WebPbn>().infer_scheme(),
             ParserKind::NonogramsOrg => self.cast::<NonogramsOrg>().infer_scheme(),
             ParserKind::Olsak => self.cast::<OlsakParser>().infer_scheme(),
+            ParserKind::Non => self.cast::<NonParser>().infer_scheme(),
+            ParserKind::AsciiArt => self.cast::<AsciiArtParser>().infer_scheme(),
             ParserKind::Simple => self.cast::<SimpleParser>().infer_scheme(),
         }
     }
+
+    fn color_count(&self) -> Result<usize, ParseError> {
+        match self.parser_kind {
+            ParserKind::Toml => self.cast::<MyFormat>().color_count(),
+            ParserKind::Json => self.cast::<JsonParser>().color_count(),
+            ParserKind::WebPbn => self.cast::<WebPbn>().color_count(),
+            ParserKind::NonogramsOrg => self.cast::<NonogramsOrg>().color_count(),
+            ParserKind::Olsak => self.cast::<OlsakParser>().color_count(),
+            ParserKind::Non => self.cast::<NonParser>().color_count(),
+            ParserKind::AsciiArt => self.cast::<AsciiArtParser>().color_count(),
+            ParserKind::Simple => self.cast::<SimpleParser>().color_count(),
+        }
+    }
 }
 
 impl fmt::Debug for DetectedParser {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         let inner = match self.parser_kind {
             ParserKind::Toml => format!("{:?}", self.cast::<MyFormat>()),
+            ParserKind::Json => format!("{:?}", self.cast::<JsonParser>()),
             ParserKind::WebPbn => format!("{:?}", self.cast::<WebPbn>()),
             ParserKind::NonogramsOrg => format!("{:?}", self.cast::<NonogramsOrg>()),
             ParserKind::Olsak => format!("{:?}", self.cast::<OlsakParser>()),
+            ParserKind::Non => format!("{:?}", self.cast::<NonParser>()),
+            ParserKind::AsciiArt => format!("{:?}", self.cast::<AsciiArtParser>()),
             ParserKind::Simple => format!("{:?}", self.cast::<SimpleParser>()),
         };
 
@@ -877,6 +1768,30 @@ impl fmt::Debug for DetectedParser {
     }
 }
 
+impl<B> Board<B>
+where
+    B: Block,
+{
+    /// Detect the format of the content and parse it into the board.
+    ///
+    /// Fails if the puzzle scheme does not match the requested block type,
+    /// e.g. when parsing a multicolor puzzle with the `BinaryBlock`.
+    pub fn try_from_str(content: &str) -> Result<Self, ParseError> {
+        DetectedParser::with_content(content)?.try_parse()
+    }
+}
+
+impl<B> TryFrom<&str> for Board<B>
+where
+    B: Block,
+{
+    type Error = ParseError;
+
+    fn try_from(content: &str) -> Result<Self, Self::Error> {
+        Self::try_from_str(content)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct OlsakColor {
     pub block_name: String,
@@ -969,12 +1884,14 @@ impl BoardParser for OlsakParser {
         })
     }
 
-    fn parse<B>(&self) -> Board<B>
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
     where
         B: Block,
     {
         let palette = self.get_palette();
-        Board::with_descriptions_and_palette(
+        new_board(
             self.parse_clues(&self.rows, &palette),
             self.parse_clues(&self.columns, &palette),
             Some(palette),
@@ -1001,7 +1918,7 @@ impl OlsakParser {
         B: Block,
     {
         let mut as_chars = block.chars();
-        let value_color_pos = as_chars.position(|c| !c.is_digit(10));
+        let value_color_pos = as_chars.position(|c| !c.is_ascii_digit());
 
         #[allow(clippy::option_if_let_else)]
         let (value, block_color) = if let Some(pos) = value_color_pos {
@@ -1223,12 +2140,14 @@ impl BoardParser for SimpleParser {
         })
     }
 
-    fn parse<B>(&self) -> Board<B>
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
     where
         B: Block,
     {
         let palette = self.get_palette();
-        Board::with_descriptions_and_palette(
+        new_board(
             Self::parse_clues(&self.rows),
             Self::parse_clues(&self.columns),
             Some(palette),
@@ -1250,12 +2169,347 @@ impl Paletted for SimpleParser {
     }
 }
 
+/// The `.non` format by Steve Simpson: the `width` and `height` keywords
+/// and the `rows` and `columns` sections with a line of comma-separated clues per line
+/// (the empty line has the single `0` clue).
+///
+/// All the other keywords (`title`, `catalogue`, `goal`, etc) are ignored.
+///
+/// ```text
+/// width 3
+/// height 2
+///
+/// rows
+/// 1,1
+/// 0
+///
+/// columns
+/// 1
+/// 0
+/// 1
+/// ```
+#[derive(Debug)]
+pub struct NonParser {
+    rows: Vec<Vec<String>>,
+    columns: Vec<Vec<String>>,
+}
+
+impl NonParser {
+    fn is_keyword_line(line: &str) -> bool {
+        line.starts_with(|ch: char| ch.is_ascii_alphabetic())
+    }
+
+    fn looks_like_non(lines: &[&str]) -> bool {
+        lines
+            .iter()
+            .any(|line| line.starts_with("width ") || line.starts_with("height "))
+    }
+
+    /// Collect the clue lines following the section header
+    /// until the empty line, the next keyword or the end of the content.
+    fn section(lines: &[&str], name: &str) -> Result<Vec<Vec<String>>, ParseError> {
+        let start = lines
+            .iter()
+            .position(|&line| line == name)
+            .ok_or_else(|| ParseError(format!("Section {:?} not found", name)))?;
+
+        Ok(lines[start + 1..]
+            .iter()
+            .take_while(|&&line| !line.is_empty() && !Self::is_keyword_line(line))
+            .map(|line| {
+                line.split(&[' ', ','][..])
+                    .filter(|block| !block.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn check_size(
+        lines: &[&str],
+        keyword: &str,
+        clues: &[Vec<String>],
+        section: &str,
+    ) -> Result<(), ParseError> {
+        let size = lines.iter().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            if parts.next() == Some(keyword) {
+                parts.next()
+            } else {
+                None
+            }
+        });
+
+        if let Some(size) = size {
+            let size: usize = size.parse()?;
+            if size != clues.len() {
+                return Err(ParseError(format!(
+                    "The {} is {}, but {} {} found",
+                    keyword,
+                    size,
+                    clues.len(),
+                    section
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BoardParser for NonParser {
+    fn with_content(content: &str) -> Result<Self, ParseError> {
+        let lines: Vec<_> = content.lines().map(str::trim).collect();
+
+        let rows = Self::section(&lines, "rows")?;
+        let columns = Self::section(&lines, "columns")?;
+        Self::check_size(&lines, "height", &rows, "rows")?;
+        Self::check_size(&lines, "width", &columns, "columns")?;
+
+        Ok(Self { rows, columns })
+    }
+
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
+    where
+        B: Block,
+    {
+        let palette = self.get_palette();
+        new_board(
+            SimpleParser::parse_clues(&self.rows),
+            SimpleParser::parse_clues(&self.columns),
+            Some(palette),
+        )
+    }
+
+    fn infer_scheme(&self) -> PuzzleScheme {
+        PuzzleScheme::BlackAndWhite
+    }
+}
+
+impl Paletted for NonParser {
+    fn get_colors(&self) -> Vec<(String, char, String)> {
+        vec![]
+    }
+
+    fn get_palette(&self) -> ColorPalette {
+        self.default_palette("white", "black")
+    }
+}
+
+/// The puzzle drawn as its solution: every character is a cell,
+/// the `.` is blank and any other (non-whitespace) character is filled.
+///
+/// A single fill character makes the black-and-white puzzle,
+/// otherwise every distinct character becomes a separate color.
+///
+/// ```text
+/// .##.
+/// #..#
+/// .##.
+/// ```
+#[derive(Debug)]
+pub struct AsciiArtParser {
+    solution_matrix: Vec<Vec<ColorId>>,
+    // the fill characters in the order of their color IDs
+    symbols: Vec<char>,
+}
+
+impl AsciiArtParser {
+    pub const BLANK: char = '.';
+
+    /// Every color is a separate bit of the `ColorId` with the lowest one for the blank.
+    const MAX_SYMBOLS: usize = 30;
+
+    /// Some arbitrary but distinguishable colors for the fill characters.
+    const COLOR_VALUES: [&'static str; 8] = [
+        "black", "red", "green", "blue", "yellow", "magenta", "cyan", "gray",
+    ];
+
+    /// The brightest channel of the generated colors differs
+    /// from any channel of the `COLOR_VALUES` (0, 128 or 255).
+    const GENERATED_BRIGHTNESS: usize = 0x99;
+
+    /// The named color for the first fill characters.
+    /// The rest get the darker hues spread evenly over the color wheel,
+    /// so every fill character has its own color.
+    fn color_value(index: usize) -> String {
+        if let Some(name) = Self::COLOR_VALUES.get(index) {
+            return (*name).to_string();
+        }
+
+        let max = Self::GENERATED_BRIGHTNESS;
+        let generated = Self::MAX_SYMBOLS - Self::COLOR_VALUES.len();
+        // walk along the six edges of the RGB cube between the pure colors
+        let hue = (index - Self::COLOR_VALUES.len()) * 6 * max / generated;
+        let (edge, offset) = (hue / max, hue % max);
+        let (r, g, b) = match edge {
+            0 => (max, offset, 0),
+            1 => (max - offset, max, 0),
+            2 => (0, max, offset),
+            3 => (0, max - offset, max),
+            4 => (offset, 0, max),
+            _ => (max, 0, max - offset),
+        };
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    fn art_lines(content: &str) -> Vec<&str> {
+        content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// Whether every (non-empty) line has the same length
+    /// and contains no digits and spaces which are the signs of the clues.
+    fn looks_like_art(content: &str) -> bool {
+        let lines = Self::art_lines(content);
+        let width = match lines.first() {
+            Some(first) => first.chars().count(),
+            None => return false,
+        };
+
+        lines.iter().all(|line| {
+            line.chars().count() == width
+                && line
+                    .chars()
+                    .all(|ch| !ch.is_whitespace() && !ch.is_ascii_digit())
+        })
+    }
+
+    fn id_by_symbol(&self, symbol: char) -> ColorId {
+        if symbol == Self::BLANK {
+            return ColorPalette::WHITE_ID;
+        }
+
+        let index = self
+            .symbols
+            .iter()
+            .position(|&fill| fill == symbol)
+            .expect("Every symbol should be collected");
+        // the same IDs as assigned by the `ColorPalette::color_with_name_value_and_symbol`
+        ColorPalette::WHITE_ID << (index + 1)
+    }
+}
+
+impl BoardParser for AsciiArtParser {
+    fn with_content(content: &str) -> Result<Self, ParseError> {
+        let lines = Self::art_lines(content);
+        if lines.is_empty() {
+            return Err(ParseError("The ASCII art is empty".to_string()));
+        }
+
+        let width = lines[0].chars().count();
+        if let Some(line) = lines.iter().find(|line| line.chars().count() != width) {
+            return Err(ParseError(format!(
+                "The ASCII art line {:?} has different length than the first one ({})",
+                line, width
+            )));
+        }
+
+        if let Some(ch) = lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .find(|ch| ch.is_whitespace())
+        {
+            return Err(ParseError(format!(
+                "Unexpected whitespace {:?} in the ASCII art",
+                ch
+            )));
+        }
+
+        let mut symbols: Vec<char> = lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .filter(|&ch| ch != Self::BLANK)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        symbols.sort_unstable();
+        if symbols.len() > Self::MAX_SYMBOLS {
+            return Err(ParseError(format!(
+                "Too many colors in the ASCII art: {}",
+                symbols.len()
+            )));
+        }
+
+        let mut this = Self {
+            solution_matrix: vec![],
+            symbols,
+        };
+        this.solution_matrix = lines
+            .iter()
+            .map(|line| line.chars().map(|ch| this.id_by_symbol(ch)).collect())
+            .collect();
+        Ok(this)
+    }
+
+    parse_by_build!();
+
+    fn build<B>(&self) -> Result<Board<B>, ParseError>
+    where
+        B: Block,
+    {
+        let (columns, rows) = clues_from_solution(&self.solution_matrix, ColorPalette::WHITE_ID);
+        new_board(rows, columns, Some(self.get_palette()))
+    }
+
+    fn infer_scheme(&self) -> PuzzleScheme {
+        if self.symbols.len() > 1 {
+            PuzzleScheme::MultiColor
+        } else {
+            PuzzleScheme::BlackAndWhite
+        }
+    }
+
+    fn color_count(&self) -> Result<usize, ParseError> {
+        Ok(self.symbols.len().max(1))
+    }
+}
+
+impl LocalReader for AsciiArtParser {}
+
+impl Paletted for AsciiArtParser {
+    fn get_colors(&self) -> Vec<(String, char, String)> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .map(|(index, &symbol)| (symbol.to_string(), symbol, Self::color_value(index)))
+            .collect()
+    }
+
+    fn get_palette(&self) -> ColorPalette {
+        let mut palette = ColorPalette::with_white("white");
+        // keep the order of the symbols to match the IDs in the solution matrix
+        for (name, symbol, value) in self.get_colors() {
+            palette.color_with_name_value_and_symbol(&name, ColorValue::parse(&value), symbol);
+        }
+
+        if let Some(first) = self.symbols.first() {
+            palette.set_default(&first.to_string()).unwrap();
+        }
+        palette
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "ini")]
 mod tests {
-    use crate::block::{base::color::ColorPalette, binary::BinaryBlock, Description};
+    use std::{collections::HashSet, fs};
 
-    use super::{BoardParser, MyFormat, Paletted, PuzzleScheme};
+    use crate::block::{
+        base::color::ColorPalette,
+        binary::{BinaryBlock, BinaryColor},
+        multicolor::{ColoredBlock, MultiColor},
+        Color, Description,
+    };
+
+    use super::{
+        AsciiArtParser, BoardParser, DetectedParser, LocalReader, MyFormat, NonParser, OlsakParser,
+        Paletted, ParserKind, PuzzleScheme, SimpleParser,
+    };
 
     const fn block(n: usize) -> BinaryBlock {
         BinaryBlock(n)
@@ -1444,4 +2698,557 @@ mod tests {
         let colors = vec![("g".to_string(), '%', "0, 204, 0".to_string())];
         assert_eq!(f.get_colors(), colors)
     }
+
+    #[test]
+    fn partial_solution() {
+        let s = r"
+        [clues]
+        rows = '1, 1'
+        columns = '1, 1'
+
+        [solution]
+        rows = '''
+        X?
+        ??
+        '''
+        ";
+
+        let board = MyFormat::with_content(s).unwrap().parse::<BinaryBlock>();
+        assert_eq!(
+            board.make_snapshot(),
+            vec![
+                BinaryColor::Black,
+                BinaryColor::Undefined,
+                BinaryColor::Undefined,
+                BinaryColor::Undefined,
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_solution_colored() {
+        let s = r"
+        [clues]
+        rows = '1g, 1'
+        columns = '1g, 1'
+
+        [colors]
+        defs = ['g=(0, 204, 0) %']
+
+        [solution]
+        rows = '''
+        % ?
+        . ?
+        '''
+        ";
+
+        let board = MyFormat::with_content(s).unwrap().parse::<ColoredBlock>();
+        let cells = board.make_snapshot();
+        assert_eq!(cells[0], MultiColor(4));
+        assert_eq!(cells[2], MultiColor(ColorPalette::WHITE_ID));
+        assert!(!cells[1].is_solved());
+        assert!(!cells[3].is_solved());
+    }
+
+    #[test]
+    fn partial_solution_unknown_symbol() {
+        let s = r"
+        [clues]
+        rows = '1'
+        columns = '1'
+
+        [solution]
+        rows = '*'
+        ";
+
+        let parser = MyFormat::with_content(s).unwrap();
+        assert!(parser.try_parse::<BinaryBlock>().is_err());
+    }
+
+    #[test]
+    fn partial_solution_wrong_size() {
+        let s = r"
+        [clues]
+        rows = '1, 1'
+        columns = '1, 1'
+
+        [solution]
+        rows = '''
+        X?
+        '''
+        ";
+
+        let parser = MyFormat::with_content(s).unwrap();
+        assert!(parser.try_parse::<BinaryBlock>().is_err());
+    }
+
+    #[test]
+    fn olsak_colored_empty_line() {
+        let content = "# the second row is empty
+#d
+   0:   #FFFFFF   white
+   a:%  #FF0000   red
+: rows
+1a
+0
+: columns
+1a
+";
+        let parser = OlsakParser::with_content(content).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::MultiColor);
+
+        let board = parser.parse::<ColoredBlock>();
+        let empty_row = board.description(crate::board::LinePosition::Row(1));
+        assert!(empty_row.is_empty());
+        assert_eq!(
+            empty_row.exact_line(board.width()),
+            Some(vec![MultiColor(ColorPalette::WHITE_ID)])
+        );
+    }
+
+    #[test]
+    fn fallback_to_other_parser() {
+        // the header is not recognized because of the comment
+        let content = "[clues] # the smallest puzzle
+rows = '1'
+columns = '1'
+";
+        assert!(DetectedParser::with_content(content).is_err());
+
+        let parser = DetectedParser::with_content_fallback(content).unwrap();
+        assert_eq!(parser.parser_kind, ParserKind::Toml);
+        assert_eq!(
+            parser
+                .parse::<BinaryBlock>()
+                .description(crate::board::LinePosition::Row(0))
+                .vec,
+            vec![block(1)]
+        );
+    }
+
+    #[test]
+    fn fallback_collects_errors() {
+        let err = DetectedParser::with_content_fallback("nothing to parse here").unwrap_err();
+        let lines: Vec<_> = err.0.lines().collect();
+        assert_eq!(lines[0], "None of the parsers succeeded:");
+        assert!(lines[1].starts_with("Simple: "));
+        assert!(lines[2].starts_with("Toml: "));
+        assert!(lines[3].starts_with("Json: "));
+        assert!(lines[4].starts_with("WebPbn: "));
+        assert!(lines[5].starts_with("NonogramsOrg: "));
+        assert!(lines[6].starts_with("Olsak: "));
+        assert!(lines[7].starts_with("Non: "));
+        assert!(lines[8].starts_with("AsciiArt: "));
+    }
+
+    #[test]
+    fn kind_by_extension() {
+        assert_eq!(
+            ParserKind::by_extension("examples/hello.toml"),
+            Some(ParserKind::Toml)
+        );
+        assert_eq!(
+            ParserKind::by_extension("/tmp/1.XML"),
+            Some(ParserKind::WebPbn)
+        );
+        assert_eq!(ParserKind::by_extension("a.non"), Some(ParserKind::Non));
+        assert_eq!(ParserKind::by_extension("a.g"), Some(ParserKind::Olsak));
+        assert_eq!(ParserKind::by_extension("a.txt"), None);
+        assert_eq!(ParserKind::by_extension("toml"), None);
+    }
+
+    #[test]
+    fn read_local_with_hint() {
+        let parser = DetectedParser::read_local_with_hint("examples/hello.toml").unwrap();
+        assert_eq!(parser.parser_kind, ParserKind::Toml);
+
+        // unknown extension: sniff the content
+        let path = std::env::temp_dir().join("nonogrid_read_local_with_hint.txt");
+        fs::write(&path, "[clues]\nrows = '1'\ncolumns = '1'\n").unwrap();
+        let parser = DetectedParser::read_local(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(parser.parser_kind, ParserKind::Toml);
+    }
+
+    #[test]
+    fn read_gzipped() {
+        // `[clues]\nrows = '1'\ncolumns = '1'\n` compressed with `gzip -n`
+        let compressed: &[u8] = &[
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x8b, 0x4e, 0xce, 0x29,
+            0x4d, 0x2d, 0x8e, 0xe5, 0x2a, 0xca, 0x2f, 0x2f, 0x56, 0xb0, 0x55, 0x50, 0x37, 0x54,
+            0xe7, 0x4a, 0xce, 0xcf, 0x29, 0xcd, 0xcd, 0x83, 0xf1, 0x00, 0x11, 0x6e, 0x57, 0x31,
+            0x21, 0x00, 0x00, 0x00,
+        ];
+        let path = std::env::temp_dir().join("nonogrid_read_gzipped.toml.gz");
+        fs::write(&path, compressed).unwrap();
+        let parser = MyFormat::read_local(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        if cfg!(feature = "gzip") {
+            let board = parser.unwrap().parse::<BinaryBlock>();
+            assert_eq!(board.width(), 1);
+            assert_eq!(board.height(), 1);
+        } else {
+            assert!(parser.unwrap_err().0.contains("--features=gzip"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "xml")]
+    fn read_local_with_hint_xml_without_declaration() {
+        let board = MyFormat::read_local("examples/hello.toml")
+            .unwrap()
+            .parse::<BinaryBlock>();
+        let xml = super::WebPbn::export(&board);
+        let xml = xml.trim_start_matches(r#"<?xml version="1.0"?>"#);
+        assert_eq!(ParserKind::detect(xml), ParserKind::Simple);
+
+        let path = std::env::temp_dir().join("nonogrid_read_local_with_hint.xml");
+        fs::write(&path, xml).unwrap();
+        let parser = DetectedParser::read_local_with_hint(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(parser.parser_kind, ParserKind::WebPbn);
+        assert!(parser.parse::<BinaryBlock>().clues_equal(&board));
+    }
+
+    #[test]
+    fn ascii_art_binary() {
+        let content = "
+.##.
+#..#
+";
+        assert_eq!(ParserKind::detect(content), ParserKind::AsciiArt);
+
+        let parser = DetectedParser::with_content(content).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::BlackAndWhite);
+        assert_eq!(parser.color_count().unwrap(), 1);
+
+        let board = parser.parse::<BinaryBlock>();
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Row)[1].vec,
+            vec![block(1), block(1)]
+        );
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Column)[1].vec,
+            vec![block(1)]
+        );
+    }
+
+    #[test]
+    fn ascii_art_colored() {
+        let parser = AsciiArtParser::with_content("rr.\n.gg").unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::MultiColor);
+        assert_eq!(parser.color_count().unwrap(), 2);
+
+        let board = parser.parse::<ColoredBlock>();
+        let green = board.palette().unwrap().id_by_name("g").unwrap();
+        let red = board.palette().unwrap().id_by_name("r").unwrap();
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Row)[0].vec,
+            vec![ColoredBlock::from_size_and_color(2, red)]
+        );
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Column)[1].vec,
+            vec![
+                ColoredBlock::from_size_and_color(1, red),
+                ColoredBlock::from_size_and_color(1, green),
+            ]
+        );
+        assert_eq!(board.desc_by_id(green).unwrap().symbol(), "g");
+    }
+
+    #[test]
+    fn ascii_art_distinct_colors() {
+        let symbols: String = ('A'..='Z').chain('a'..='d').collect();
+        let parser = AsciiArtParser::with_content(&symbols).unwrap();
+        assert_eq!(parser.color_count().unwrap(), AsciiArtParser::MAX_SYMBOLS);
+
+        let board = parser.parse::<ColoredBlock>();
+        let palette = board.palette().unwrap();
+        let values: HashSet<_> = symbols
+            .chars()
+            .map(|symbol| {
+                let id = palette.id_by_name(&symbol.to_string()).unwrap();
+                board.desc_by_id(id).unwrap().rgb_value()
+            })
+            .collect();
+        assert_eq!(values.len(), symbols.len());
+        assert!(!values.contains(&(255, 255, 255)));
+
+        // only the first symbol is black
+        let black = board.desc_by_id(palette.id_by_name("A").unwrap()).unwrap();
+        assert_eq!(black.rgb_value(), (0, 0, 0));
+    }
+
+    #[test]
+    fn clue_text_round_trip() {
+        use crate::{block::base::clues_from_solution, board::Board};
+
+        let (columns, rows) = clues_from_solution(&[vec![1, 0, 1], vec![0, 0, 0]], 0);
+        let board: Board<BinaryBlock> = Board::with_descriptions_and_palette(rows, columns, None);
+
+        let text = board.to_clue_text();
+        assert_eq!(ParserKind::detect(&text), ParserKind::Non);
+        let parsed = SimpleParser::with_content(&text)
+            .unwrap()
+            .parse::<BinaryBlock>();
+        assert!(parsed.clues_equal(&board));
+        assert_eq!(parsed.to_clue_text(), text);
+
+        let parsed = NonParser::with_content(&text)
+            .unwrap()
+            .parse::<BinaryBlock>();
+        assert!(parsed.clues_equal(&board));
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_binary() {
+        let content = r#"{"rows": [[1, 1], []], "columns": [[1], [], [1]]}"#;
+        assert_eq!(ParserKind::detect(content), ParserKind::Json);
+
+        let parser = DetectedParser::with_content(content).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::BlackAndWhite);
+
+        let board = parser.parse::<BinaryBlock>();
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Row)[0].vec,
+            vec![block(1), block(1)]
+        );
+        assert_eq!(board.width(), 3);
+        assert_eq!(board.height(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_colored() {
+        use super::JsonParser;
+
+        let content = r##"{
+            "rows": [[{"size": 1, "color": "r"}, 1]],
+            "columns": [[{"size": 1, "color": "r"}], [1]],
+            "colors": {"r": "#f00"}
+        }"##;
+        let parser = JsonParser::with_content(content).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::MultiColor);
+
+        let board = parser.parse::<ColoredBlock>();
+        let palette = board.palette().unwrap();
+        let red = palette.id_by_name("r").unwrap();
+        let black = palette.id_by_name("black").unwrap();
+        assert_eq!(palette.get("r").unwrap().rgb_value(), (255, 0, 0));
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Row)[0].vec,
+            vec![
+                ColoredBlock::from_size_and_color(1, red),
+                ColoredBlock::from_size_and_color(1, black),
+            ]
+        );
+
+        let err =
+            JsonParser::with_content(r#"{"rows": [[{"size": 1, "color": "g"}]], "columns": []}"#)
+                .unwrap_err();
+        assert_eq!(err.0, r#"Unknown block color "g""#);
+    }
+
+    #[test]
+    fn my_format_to_toml_binary() {
+        let board = MyFormat::with_content(
+            r#"
+            [clues]
+            rows = "1 1, 0"
+            columns = "1, 0, 1"
+            "#,
+        )
+        .unwrap()
+        .parse::<BinaryBlock>();
+
+        let toml = MyFormat::to_toml(&board);
+        assert_eq!(toml, "[clues]\nrows = \"1 1, 0\"\ncolumns = \"1, 0, 1\"");
+
+        let parsed = MyFormat::with_content(&toml).unwrap();
+        assert_eq!(parsed.infer_scheme(), PuzzleScheme::BlackAndWhite);
+        assert!(parsed.parse::<BinaryBlock>().clues_equal(&board));
+    }
+
+    #[test]
+    fn my_format_to_toml_colored() {
+        let board = MyFormat::with_content(
+            r#"
+            [clues]
+            rows = "1r 1b, 1"
+            columns = "1r, 1b 1"
+
+            [colors]
+            defs = ["r = (red) %", "b = (0, 0, 255) *"]
+            "#,
+        )
+        .unwrap()
+        .parse::<ColoredBlock>();
+
+        let toml = MyFormat::to_toml(&board);
+        assert!(toml.contains(r#"rows = "1r 1b, 1""#));
+        assert!(toml.contains(r#"defs = ["b = (0, 0, 255) *", "r = (255, 0, 0) %"]"#));
+
+        let parser = MyFormat::with_content(&toml).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::MultiColor);
+        let parsed = parser.parse::<ColoredBlock>();
+        assert!(parsed.clues_equal(&board));
+        assert_eq!(MyFormat::to_toml(&parsed), toml);
+    }
+
+    #[test]
+    #[cfg(feature = "xml")]
+    fn webpbn_export_binary() {
+        use super::WebPbn;
+
+        let board = MyFormat::with_content(
+            r#"
+            [clues]
+            rows = "1 1, 0"
+            columns = "1, 0, 1"
+            "#,
+        )
+        .unwrap()
+        .parse::<BinaryBlock>();
+
+        let xml = WebPbn::export(&board);
+        assert!(xml.contains(
+            "<clues type=\"rows\">\n<line><count>1</count><count>1</count></line>\n<line></line>\n</clues>"
+        ));
+
+        let parser = WebPbn::with_content(&xml).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::BlackAndWhite);
+        assert!(parser.parse::<BinaryBlock>().clues_equal(&board));
+    }
+
+    #[test]
+    #[cfg(feature = "xml")]
+    fn webpbn_export_colored() {
+        use super::WebPbn;
+
+        let board = MyFormat::with_content(
+            r#"
+            [clues]
+            rows = "1r 1b, 1"
+            columns = "1r, 1b 1"
+
+            [colors]
+            defs = ["r = (red) %", "b = (blue) *"]
+            "#,
+        )
+        .unwrap()
+        .parse::<ColoredBlock>();
+
+        let xml = WebPbn::export(&board);
+        assert!(xml.contains(r#"<puzzle type="grid" defaultcolor="black">"#));
+        assert!(xml.contains(r#"<color name="red" char="%">ff0000</color>"#));
+        assert!(xml
+            .contains(r#"<line><count color="red">1</count><count color="blue">1</count></line>"#));
+
+        let parser = WebPbn::with_content(&xml).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::MultiColor);
+        let parsed = parser.parse::<ColoredBlock>();
+        assert!(parsed.clues_equal(&board));
+        assert_eq!(WebPbn::export(&parsed), xml);
+    }
+
+    #[test]
+    fn nonograms_org_cypher() {
+        use super::NonogramsOrg;
+
+        let cypher = "[0,0,0,0],[2,0,0,100],[1,0,0,100],[1,0,0,100],[0,0,0,0],\
+                      [0,0,0,0],[0,0,1,100],[0,0,0,0],[1,2,1,1]";
+
+        let parser = NonogramsOrg::with_content(&format!("var d=[{}];", cypher)).unwrap();
+        let (colors, solution) = parser.decipher().unwrap();
+        assert_eq!(colors, ["000000"]);
+        assert_eq!(solution, [[1, 1]]);
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::BlackAndWhite);
+
+        // the block is missing
+        let truncated = cypher.trim_end_matches(",[1,2,1,1]");
+        let err = NonogramsOrg::with_content(&format!("var d=[{}];", truncated)).unwrap_err();
+        assert_eq!(err.0, "Malformed cypher: the item 8 is missing");
+
+        let err = NonogramsOrg::with_content("var d=[[1,2,3,4],[5,6]];").unwrap_err();
+        assert_eq!(err.0, "Malformed cypher: the item 1 is too short");
+
+        assert!(NonogramsOrg::with_content("var d=[[1,2,3,4],[5,x]];").is_err());
+    }
+
+    #[test]
+    fn nonograms_org_cypher_large_modulus() {
+        use super::NonogramsOrg;
+
+        // the remainders of the width sum up beyond the `u16`
+        let cypher = "[0,0,0,0],[59999,59999,0,60000],[1,0,0,100],[1,0,0,100],[0,0,0,0],\
+                      [0,0,0,0],[0,0,1,100],[0,0,0,0],[1,2,1,1]";
+        let parser = NonogramsOrg::with_content(&format!("var d=[{}];", cypher)).unwrap();
+        let (_colors, solution) = parser.decipher().unwrap();
+        assert_eq!(solution[0].len(), 119_998);
+
+        let cypher = cypher.replace(
+            "[1,0,0,100],[1,0,0,100]",
+            "[59999,59999,0,60000],[1,0,0,100]",
+        );
+        let err = NonogramsOrg::with_content(&format!("var d=[{}];", cypher)).unwrap_err();
+        assert_eq!(err.0, "Malformed cypher: the board is too big");
+
+        // the solution size (with the squared remainder) overflows the `u16`
+        let cypher = "[0,0,0,0],[2,0,0,100],[1,0,0,100],[1,0,0,100],[0,0,0,0],\
+                      [0,0,0,0],[59999,0,0,60000],[0,0,0,0],[1,2,1,1]";
+        let err = NonogramsOrg::with_content(&format!("var d=[{}];", cypher)).unwrap_err();
+        assert_eq!(err.0, "Malformed cypher: the item 9 is missing");
+    }
+
+    #[test]
+    fn non_format() {
+        let content = r#"
+catalogue "webpbn.com #1"
+title "Demo"
+width 3
+height 2
+
+rows
+1,1
+0
+
+columns
+1
+0
+1
+goal "101000"
+"#;
+        assert_eq!(ParserKind::detect(content), ParserKind::Non);
+
+        let parser = DetectedParser::with_content(content).unwrap();
+        assert_eq!(parser.infer_scheme(), PuzzleScheme::BlackAndWhite);
+
+        let board = parser.parse::<BinaryBlock>();
+        assert_eq!(
+            board.descriptions(crate::board::LineDirection::Row)[0].vec,
+            vec![block(1), block(1)]
+        );
+        assert!(board.descriptions(crate::board::LineDirection::Row)[1]
+            .vec
+            .is_empty());
+        assert_eq!(board.width(), 3);
+    }
+
+    #[test]
+    fn non_format_errors() {
+        assert!(NonParser::with_content("width 1\nheight 1\nrows\n1").is_err());
+        assert!(NonParser::with_content("width 2\nheight 1\nrows\n1\ncolumns\n1").is_err());
+    }
+
+    #[test]
+    fn ascii_art_errors() {
+        assert!(AsciiArtParser::with_content("\n\n").is_err());
+        assert!(AsciiArtParser::with_content("##\n#").is_err());
+        assert!(AsciiArtParser::with_content("# #\n###").is_err());
+
+        // the clues with digits are not the art
+        assert_eq!(ParserKind::detect("1\n2"), ParserKind::Simple);
+    }
 }
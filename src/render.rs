@@ -1,12 +1,20 @@
-use std::fmt::{self, Display};
+use std::{
+    fmt::{self, Display},
+    io,
+    marker::PhantomData,
+};
 
 #[cfg(feature = "colored")]
 use colored::{self, ColoredString, Colorize};
 use hashbrown::HashMap;
 
 use crate::{
-    block::{base::color::ColorDesc, binary::BinaryColor, Block, Color, Description},
-    board::{Board, LineDirection},
+    block::{
+        base::color::{ColorDesc, ColorId, ColorPalette},
+        binary::{BinaryBlock, BinaryColor},
+        Block, Color, Description,
+    },
+    board::{Board, LineDirection, Point},
     utils::{
         pad, pad_with,
         rc::{MutRc, ReadRc, ReadRef},
@@ -22,6 +30,12 @@ where
     fn render(&self) -> String;
     fn render_simple(&self) -> String;
 
+    /// Same as the `render`, but write the result into the `w`.
+    /// The renderers can override it to avoid building the whole string.
+    fn render_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        w.write_all(self.render().as_bytes())
+    }
+
     fn concat(rows: impl Iterator<Item = Vec<String>>) -> String {
         let rows: Vec<_> = rows.map(|line| line.concat()).collect();
         rows.join("\n")
@@ -34,6 +48,10 @@ where
     B: Block,
 {
     board: MutRc<Board<B>>,
+    legend: bool,
+    white_symbol: Option<char>,
+    black_symbol: Option<char>,
+    unknown_symbol: Option<char>,
 }
 
 impl<B> Renderer<B> for ShellRenderer<B>
@@ -42,10 +60,24 @@ where
     B::Color: Display,
 {
     fn with_board(board: MutRc<Board<B>>) -> Self {
-        Self { board }
+        Self {
+            board,
+            legend: false,
+            white_symbol: None,
+            black_symbol: None,
+            unknown_symbol: None,
+        }
     }
 
     fn render(&self) -> String {
+        let mut rendered = Vec::new();
+        self.render_to(&mut rendered)
+            .expect("Writing into the memory should not fail");
+        String::from_utf8(rendered).expect("The rendered board should be a valid UTF-8")
+    }
+
+    /// Write the board line by line, so only a single line is kept in the memory at once.
+    fn render_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
         let full_width = self.side_width() + self.board().width();
 
         let mut header = self.header_lines();
@@ -64,12 +96,26 @@ where
             .into_iter()
             .map(|row| row.into_iter().map(|s| ColoredString::from(s.as_str())));
 
-        let grid = self.grid_lines();
-        let grid = side.zip(grid).map(|(s, g)| s.chain(g).collect());
+        let board = self.board();
+        let mut color_cache = HashMap::new();
+        let grid = board
+            .iter_rows()
+            .map(|row| self.grid_line(row, &mut color_cache));
+        let grid = side.zip(grid).map(|(s, g)| s.chain(g).collect::<Vec<_>>());
+
+        for (i, line) in header.chain(grid).enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            for symbol in &line {
+                write!(w, "{}", pad(symbol, 2, true))?;
+            }
+        }
 
-        Self::concat(header.chain(grid).map(|line: Vec<ColoredString>| {
-            line.iter().map(|symbol| pad(symbol, 2, true)).collect()
-        }))
+        if let Some(legend) = self.legend_line() {
+            write!(w, "\n{}", legend)?;
+        }
+        Ok(())
     }
 
     fn render_simple(&self) -> String {
@@ -85,10 +131,109 @@ impl<B> ShellRenderer<B>
 where
     B: Block,
 {
+    /// Append the line describing every color of the palette to the full rendering.
+    /// The legend is never shown for the black-and-white puzzles.
+    #[allow(dead_code)]
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Use the given symbol for the blank cells instead of the palette one.
+    #[allow(dead_code)]
+    pub fn with_white_symbol(mut self, symbol: char) -> Self {
+        self.white_symbol = Some(symbol);
+        self
+    }
+
+    /// Use the given symbol for the black cells instead of the palette one.
+    #[allow(dead_code)]
+    pub fn with_black_symbol(mut self, symbol: char) -> Self {
+        self.black_symbol = Some(symbol);
+        self
+    }
+
+    /// Use the given symbol for the cells that are not solved yet.
+    #[allow(dead_code)]
+    pub fn with_unknown_symbol(mut self, symbol: char) -> Self {
+        self.unknown_symbol = Some(symbol);
+        self
+    }
+
     fn board(&self) -> ReadRef<'_, Board<B>> {
         self.board.read()
     }
 
+    /// The black color is either the default color of the palette
+    /// or the one with the (0, 0, 0) RGB value, whatever its name is.
+    fn is_black(&self, color_id: ColorId) -> bool {
+        let board = self.board();
+        let palette = match board.palette() {
+            Some(palette) => palette,
+            None => return false,
+        };
+
+        let default_id = palette
+            .get_default()
+            .and_then(|name| palette.id_by_name(name));
+        if default_id == Some(color_id) {
+            return true;
+        }
+
+        palette
+            .desc_by_id(color_id)
+            .filter(|desc| desc.rgb_value() == (0, 0, 0))
+            .is_some()
+    }
+
+    fn custom_symbol(&self, cell: B::Color) -> Option<char> {
+        if !cell.is_solved() {
+            return self.unknown_symbol;
+        }
+
+        if cell == B::Color::blank() {
+            return self.white_symbol;
+        }
+
+        // the binary cells do not have a color ID: every solved non-blank one is black
+        let is_black = cell
+            .as_color_id()
+            .filter(|&color_id| !self.is_black(color_id))
+            .is_none();
+
+        if is_black {
+            self.black_symbol
+        } else {
+            None
+        }
+    }
+
+    fn legend_line(&self) -> Option<String> {
+        if !self.legend {
+            return None;
+        }
+
+        let board = self.board();
+        let colors: Vec<_> = board.palette()?.iter().collect();
+        // only the white and the black
+        if colors.len() <= 2 {
+            return None;
+        }
+
+        let legend: Vec<_> = colors
+            .into_iter()
+            .map(|color_desc| {
+                format!(
+                    "{}={} ({})",
+                    color_desc.symbol(),
+                    color_desc.name(),
+                    color_desc.hex()
+                )
+            })
+            .collect();
+        Some(legend.join(", "))
+    }
+
     fn side_width(&self) -> usize {
         Self::descriptions_width(self.board().descriptions(LineDirection::Row))
     }
@@ -159,28 +304,109 @@ where
     B: Block,
     B::Color: Display,
 {
+    /// The canonical compact form of the board: one symbol per cell, one line per row
+    /// and no clues. Unlike the `render_simple` it never uses the terminal colors
+    /// and the custom symbols, so the output is stable and suitable for the golden-file tests.
+    #[allow(dead_code)]
+    pub fn render_grid(&self) -> String {
+        self.board().to_string()
+    }
+
+    /// Render every solution in the canonical compact form (see the `render_grid`).
+    /// The board state does not change.
+    #[allow(dead_code)]
+    pub fn render_solutions(&self, solutions: &[Vec<B::Color>]) -> Vec<String> {
+        let mut board = self.board().clone();
+        solutions
+            .iter()
+            .map(|solution| {
+                board.restore(solution.clone());
+                board.to_string()
+            })
+            .collect()
+    }
+
     fn cell_symbol(&self, cell: &B::Color) -> ColoredString {
+        if let Some(symbol) = self.custom_symbol(*cell) {
+            return symbol.to_string().as_str().into();
+        }
+
+        if !cell.is_solved() {
+            return UNKNOWN_SYMBOL.to_string().as_str().into();
+        }
+
         let id = cell.as_color_id();
 
         id.and_then(|color_id| self.board().desc_by_id(color_id).map(From::from))
-            .unwrap_or_else(|| cell.to_string().as_str().into())
+            .unwrap_or_else(|| fallback_symbol(*cell).as_str().into())
+    }
+
+    fn grid_line(
+        &self,
+        row: &[B::Color],
+        color_cache: &mut HashMap<B::Color, ColoredString>,
+    ) -> Vec<ColoredString> {
+        row.iter()
+            .map(|cell| {
+                color_cache
+                    .entry(*cell)
+                    .or_insert_with(|| self.cell_symbol(cell))
+                    .clone()
+            })
+            .collect()
     }
 
     fn grid_lines(&self) -> Vec<Vec<ColoredString>> {
         let mut color_cache = HashMap::new();
         self.board()
+            .iter_rows()
+            .map(|row| self.grid_line(row, &mut color_cache))
+            .collect()
+    }
+}
+
+/// The symbol for the cells that are not solved yet.
+const UNKNOWN_SYMBOL: char = '?';
+
+/// The symbol for the solved cell which color is not found in the palette.
+/// The blank cell always gets the `.` (as in the default palette),
+/// so the cells known to be blank never look the same as the unsolved ones.
+fn fallback_symbol<C>(cell: C) -> String
+where
+    C: Color + Display,
+{
+    if cell == C::blank() {
+        ".".to_string()
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Same as the `ShellRenderer::render_grid`:
+/// a palette symbol for every solved cell and `?` for the unsolved ones.
+impl<B> Display for Board<B>
+where
+    B: Block,
+    B::Color: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self
             .iter_rows()
             .map(|row| {
                 row.iter()
                     .map(|cell| {
-                        color_cache
-                            .entry(cell)
-                            .or_insert_with(|| self.cell_symbol(cell))
-                            .clone()
+                        if !cell.is_solved() {
+                            return UNKNOWN_SYMBOL.to_string();
+                        }
+
+                        cell.as_color_id()
+                            .and_then(|color_id| self.desc_by_id(color_id))
+                            .map_or_else(|| fallback_symbol(*cell), |desc| desc.symbol())
                     })
                     .collect()
             })
-            .collect()
+            .collect();
+        write!(f, "{}", rows.join("\n"))
     }
 }
 
@@ -196,3 +422,387 @@ impl Display for BinaryColor {
         write!(f, "{}", symbol)
     }
 }
+
+/// Packs every 2x4 block of the cells into a single braille character (U+2800..U+28FF),
+/// so the large boards fit into the terminal. The black cells are the raised dots,
+/// the white and unsolved ones are not.
+///
+/// The clues are not rendered, so the `render` is the same as the `render_simple`.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct BrailleRenderer {
+    board: MutRc<Board<BinaryBlock>>,
+}
+
+impl Renderer<BinaryBlock> for BrailleRenderer {
+    fn with_board(board: MutRc<Board<BinaryBlock>>) -> Self {
+        Self { board }
+    }
+
+    fn render(&self) -> String {
+        self.render_simple()
+    }
+
+    fn render_simple(&self) -> String {
+        let board = self.board.read();
+        let rows: Vec<&[BinaryColor]> = board.iter_rows().collect();
+        let lines: Vec<String> = rows
+            .chunks(Self::BLOCK_HEIGHT)
+            .map(|rows| {
+                (0..board.width())
+                    .step_by(Self::BLOCK_WIDTH)
+                    .map(|x| Self::braille_char(rows, x))
+                    .collect()
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[allow(dead_code)]
+impl BrailleRenderer {
+    const BLOCK_WIDTH: usize = 2;
+    const BLOCK_HEIGHT: usize = 4;
+    const BLANK_CHAR: u32 = 0x2800;
+
+    /// The bit of every dot: the first column goes top-down as 1, 2, 3, 7,
+    /// the second one as 4, 5, 6, 8.
+    const DOTS: [[u32; Self::BLOCK_WIDTH]; Self::BLOCK_HEIGHT] =
+        [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    /// The missing cells of the partial block on the board edge are treated as white.
+    fn braille_char(rows: &[&[BinaryColor]], x: usize) -> char {
+        let mut code = Self::BLANK_CHAR;
+        for (row, dots) in rows.iter().zip(&Self::DOTS) {
+            for (cell, dot) in row.iter().skip(x).zip(dots) {
+                if *cell == BinaryColor::Black {
+                    code |= dot;
+                }
+            }
+        }
+        std::char::from_u32(code).expect("Every braille pattern is a valid char")
+    }
+}
+
+/// Renders nothing. Useful to measure the solving time without any formatting overhead.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct NullRenderer<B>
+where
+    B: Block,
+{
+    _board: PhantomData<B>,
+}
+
+impl<B> Renderer<B> for NullRenderer<B>
+where
+    B: Block,
+{
+    fn with_board(_board: MutRc<Board<B>>) -> Self {
+        Self {
+            _board: PhantomData,
+        }
+    }
+
+    fn render(&self) -> String {
+        String::new()
+    }
+
+    fn render_simple(&self) -> String {
+        String::new()
+    }
+}
+
+/// Renders the board as the HTML `<table>` with the clues in the header cells
+/// and the colors of the cells set with the inline styles.
+///
+/// The unsolved cells get the `unsolved` class. The full rendering
+/// defines it as the diagonal stripes, so the cells with several possible colors
+/// are distinguishable from any solved one.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct HtmlRenderer<B>
+where
+    B: Block,
+{
+    board: MutRc<Board<B>>,
+}
+
+impl<B> Renderer<B> for HtmlRenderer<B>
+where
+    B: Block + Display,
+{
+    fn with_board(board: MutRc<Board<B>>) -> Self {
+        Self { board }
+    }
+
+    fn render(&self) -> String {
+        let board = self.board();
+        let header = board
+            .descriptions(LineDirection::Column)
+            .iter()
+            .map(|desc| format!("<th>{}</th>", self.clues_html(desc, "<br>")))
+            .collect::<String>();
+
+        let rows = board
+            .descriptions(LineDirection::Row)
+            .iter()
+            .zip(self.cell_lines())
+            .map(|(desc, cells)| {
+                format!(
+                    "<tr><th>{}</th>{}</tr>",
+                    self.clues_html(desc, " "),
+                    cells.concat()
+                )
+            });
+
+        let lines: Vec<_> = vec![
+            format!("<style>{}</style>", Self::UNSOLVED_STYLE),
+            "<table>".to_string(),
+            format!("<tr><th></th>{}</tr>", header),
+        ]
+        .into_iter()
+        .chain(rows)
+        .chain(Some("</table>".to_string()))
+        .collect();
+        lines.join("\n")
+    }
+
+    /// Only the cells without the clues and the style definitions.
+    fn render_simple(&self) -> String {
+        let rows = self
+            .cell_lines()
+            .into_iter()
+            .map(|cells| format!("<tr>{}</tr>", cells.concat()));
+        let lines: Vec<_> = Some("<table>".to_string())
+            .into_iter()
+            .chain(rows)
+            .chain(Some("</table>".to_string()))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[allow(dead_code)]
+impl<B> HtmlRenderer<B>
+where
+    B: Block + Display,
+{
+    const UNSOLVED_STYLE: &'static str = "td.unsolved { background: \
+        repeating-linear-gradient(45deg, #ffffff, #ffffff 2px, #808080 2px, #808080 4px); }";
+
+    fn board(&self) -> ReadRef<'_, Board<B>> {
+        self.board.read()
+    }
+
+    /// The colored clues are painted with their colors,
+    /// the clues of the default (usually black) color are left as is.
+    fn clues_html(&self, desc: &ReadRc<Description<B>>, separator: &str) -> String {
+        let board = self.board();
+        let default_id = board
+            .palette()
+            .and_then(|palette| palette.id_by_name(palette.get_default()?));
+
+        let clues: Vec<_> = desc
+            .vec
+            .iter()
+            .map(|block| {
+                let color_desc = block
+                    .color()
+                    .as_color_id()
+                    .filter(|&color_id| Some(color_id) != default_id)
+                    .and_then(|color_id| board.desc_by_id(color_id));
+
+                match color_desc {
+                    Some(color_desc) => format!(
+                        "<span style=\"color: {}\">{}</span>",
+                        color_desc.hex(),
+                        block
+                    ),
+                    None => block.to_string(),
+                }
+            })
+            .collect();
+        clues.join(separator)
+    }
+
+    fn cell_html(board: &Board<B>, cell: B::Color, point: &Point) -> String {
+        if !cell.is_solved() {
+            return "<td class=\"unsolved\"></td>".to_string();
+        }
+
+        let color = if cell == B::Color::blank() {
+            board
+                .desc_by_id(ColorPalette::WHITE_ID)
+                .map_or_else(|| "#ffffff".to_string(), |desc| desc.hex())
+        } else {
+            board
+                .color_at(point)
+                .map_or_else(|| "#000000".to_string(), |desc| desc.hex())
+        };
+        format!("<td style=\"background-color: {}\"></td>", color)
+    }
+
+    fn cell_lines(&self) -> Vec<Vec<String>> {
+        let board = self.board();
+        board
+            .iter_rows()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, cell)| Self::cell_html(&board, *cell, &Point::new(x, y)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// the command line tool does not write the images
+#[cfg(feature = "png")]
+#[allow(unused_imports)]
+pub use self::png::PngRenderer;
+
+#[cfg(feature = "png")]
+mod png {
+    use std::{io, path::Path};
+
+    use image::{Rgb, RgbImage};
+
+    use crate::{
+        block::{base::color::ColorPalette, Block, Color},
+        board::{Board, Point},
+        utils::rc::{MutRc, ReadRef},
+    };
+
+    /// Draws the board as a raster image where every cell is a square of pixels.
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    pub struct PngRenderer<B>
+    where
+        B: Block,
+    {
+        board: MutRc<Board<B>>,
+        explicit_white: bool,
+        grid: bool,
+    }
+
+    #[allow(dead_code)]
+    impl<B> PngRenderer<B>
+    where
+        B: Block,
+    {
+        const WHITE: [u8; 3] = [255, 255, 255];
+        const BLACK: [u8; 3] = [0, 0, 0];
+        const UNKNOWN: [u8; 3] = [128, 128, 128];
+        /// Every that many cells the grid line is thicker, to ease the counting.
+        const GRID_GROUP: u32 = 5;
+
+        pub fn with_board(board: MutRc<Board<B>>) -> Self {
+            Self {
+                board,
+                explicit_white: false,
+                grid: false,
+            }
+        }
+
+        /// Draw the blank cells with the white color defined in the palette
+        /// (which is not necessarily the pure white) as any other color,
+        /// instead of leaving them as the background.
+        pub fn with_explicit_white(mut self, explicit_white: bool) -> Self {
+            self.explicit_white = explicit_white;
+            self
+        }
+
+        /// Separate the cells with the 1px black lines. The lines on the border
+        /// and between every 5 cells are 2px wide.
+        pub fn with_grid(mut self, grid: bool) -> Self {
+            self.grid = grid;
+            self
+        }
+
+        fn board(&self) -> ReadRef<'_, Board<B>> {
+            self.board.read()
+        }
+
+        // `is_multiple_of` is not available on the minimal supported Rust version
+        #[allow(clippy::manual_is_multiple_of)]
+        fn grid_line_width(boundary: u32, cells: u32) -> u32 {
+            if boundary == cells || boundary % Self::GRID_GROUP == 0 {
+                2
+            } else {
+                1
+            }
+        }
+
+        /// The first pixel of every cell along the axis and the full length of the axis.
+        fn cell_offsets(&self, cells: u32, cell_px: u32) -> (Vec<u32>, u32) {
+            if !self.grid {
+                return ((0..cells).map(|i| i * cell_px).collect(), cells * cell_px);
+            }
+
+            let mut offset = 0;
+            let offsets = (0..cells)
+                .map(|i| {
+                    offset += Self::grid_line_width(i, cells);
+                    let start = offset;
+                    offset += cell_px;
+                    start
+                })
+                .collect();
+            (offsets, offset + Self::grid_line_width(cells, cells))
+        }
+
+        fn cell_rgb(&self, board: &Board<B>, point: &Point) -> [u8; 3] {
+            let cell = board.cell(point);
+            if !cell.is_solved() {
+                return Self::UNKNOWN;
+            }
+
+            let (desc, default) = if cell == B::Color::blank() {
+                if !self.explicit_white {
+                    return Self::WHITE;
+                }
+                (board.desc_by_id(ColorPalette::WHITE_ID), Self::WHITE)
+            } else {
+                (board.color_at(point), Self::BLACK)
+            };
+
+            desc.map_or(default, |desc| {
+                let (r, g, b) = desc.rgb_value();
+                [r, g, b]
+            })
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        pub fn render_image(&self, cell_px: u32) -> RgbImage {
+            let board = self.board();
+            let width = board.width() as u32;
+            let height = board.height() as u32;
+
+            let (xs, image_width) = self.cell_offsets(width, cell_px);
+            let (ys, image_height) = self.cell_offsets(height, cell_px);
+
+            let mut image = RgbImage::from_pixel(image_width, image_height, Rgb(Self::BLACK));
+            for (y, &top) in ys.iter().enumerate() {
+                for (x, &left) in xs.iter().enumerate() {
+                    let rgb = Rgb(self.cell_rgb(&board, &Point::new(x, y)));
+                    for dy in 0..cell_px {
+                        for dx in 0..cell_px {
+                            image.put_pixel(left + dx, top + dy, rgb);
+                        }
+                    }
+                }
+            }
+            image
+        }
+
+        // `io::Error::other` is not available on the minimal supported Rust version
+        #[allow(clippy::io_other_error)]
+        pub fn render_to_path(&self, path: &Path, cell_px: u32) -> io::Result<()> {
+            self.render_image(cell_px)
+                .save(path)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+        }
+    }
+}
@@ -1,15 +1,116 @@
+use std::{
+    io::{BufRead, Write},
+    time::Instant,
+};
+
 use log::warn;
 
-use crate::{block::Block, board::Board, solver::probing::ProbeSolver, utils::rc::MutRc};
+use crate::{
+    block::{
+        binary::{BinaryBlock, BinaryColor},
+        Block, Description,
+    },
+    board::{Board, LineDirection, LinePosition, Point},
+    solver::{
+        line::{DynamicSolver, UnsolvableLine},
+        probing::{FullProbe1, ProbeSolver},
+    },
+    utils::rc::MutRc,
+};
+
+#[cfg(not(feature = "sat"))]
+use crate::utils::time;
 
 #[cfg(not(feature = "sat"))]
 pub mod backtracking;
+#[cfg(not(feature = "sat"))]
+pub mod difficulty;
 pub mod line;
 pub mod probing;
 pub mod propagation;
 #[cfg(feature = "sat")]
 pub mod sat;
 
+/// Fill the degenerate boards (e.g. all empty or all full) where every row
+/// is determined by its clues alone, without spinning up the solvers.
+///
+/// Return whether the board was filled.
+fn solve_trivial<B>(board: &MutRc<Board<B>>) -> Result<bool, String>
+where
+    B: Block,
+{
+    let rows: Option<Vec<_>> = {
+        let board = board.read();
+        let width = board.width();
+        board
+            .descriptions(LineDirection::Row)
+            .iter()
+            .map(|desc| desc.exact_line(width))
+            .collect()
+    };
+
+    let rows = match rows {
+        Some(rows) => rows,
+        None => return Ok(false),
+    };
+
+    warn!("Every row is determined by its clues: filling the board directly");
+    for (index, row) in rows.iter().enumerate() {
+        Board::set_row_with_callback(board, index, row);
+    }
+
+    let board = board.read();
+    let columns_valid = (0..board.width()).all(|index| {
+        let position = LinePosition::Column(index);
+        board
+            .description(position)
+            .matches_line(&board.get_line(position))
+    });
+
+    if columns_valid {
+        Ok(true)
+    } else {
+        Err("Bad puzzle: the columns contradict the rows".to_string())
+    }
+}
+
+/// Return whether the propagation was interrupted by the deadline.
+fn propagate<B, S>(board: &MutRc<Board<B>>, deadline: Option<Instant>) -> Result<bool, String>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+{
+    warn!("Solving with simple line propagation");
+    let mut solver = propagation::Solver::new(MutRc::clone(board));
+    if let Some(deadline) = deadline {
+        solver.set_deadline(deadline);
+    }
+
+    let solved_points = solver
+        .run::<S>(None)
+        .map_err(|err| format!("Bad puzzle: simple propagation failed: {:?}", err))?;
+    warn!("Solved {} points", solved_points.len());
+    Ok(solver.timed_out())
+}
+
+/// Propagate the consequences of the single cell changed outside of the solver
+/// (e.g. filled by the player) through its row and column and further through the affected lines.
+///
+/// Return the cells solved by the propagation (not including the changed one)
+/// or the error if the board became contradictory.
+#[allow(dead_code)]
+pub fn run_from_point<B, S>(
+    board: MutRc<Board<B>>,
+    point: Point,
+) -> Result<Vec<Point>, UnsolvableLine>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+{
+    let mut solver = propagation::Solver::new(board);
+    solver.run::<S>(Some(point))
+}
+
 #[cfg(not(feature = "sat"))]
 pub fn run<B, S, P>(
     board: MutRc<Board<B>>,
@@ -22,19 +123,28 @@ where
     S: line::LineSolver<BlockType = B>,
     P: ProbeSolver<BlockType = B>,
 {
-    warn!("Solving with simple line propagation");
-    let mut solver = propagation::Solver::new(MutRc::clone(&board));
-    let solved_points = solver
-        .run::<S>(None)
-        .map_err(|err| format!("Bad puzzle: simple propagation failed: {:?}", err))?;
+    board.read().check_clues()?;
+    if solve_trivial(&board)? {
+        return Ok(None);
+    }
 
-    warn!("Solved {} points", solved_points.len());
+    // the timeout bounds the whole solving, not only the backtracking
+    let deadline = timeout.and_then(time::deadline);
+
+    let timed_out = propagate::<B, S>(&board, deadline)?;
+    if timed_out {
+        let mut solver =
+            backtracking::Solver::<_, P, S>::with_options(board, max_solutions, timeout, max_depth);
+        solver.interrupt_in_propagation();
+        return Ok(Some(solver));
+    }
 
     if !board.read().is_solved_full() {
         warn!(
             "Trying to solve with backtracking (solved on {:.3}%)",
             board.read().solution_rate() * 100.0
         );
+        let timeout = deadline.map_or(timeout, |deadline| Some(time::seconds_left(deadline)));
         let mut solver =
             backtracking::Solver::<_, P, S>::with_options(board, max_solutions, timeout, max_depth);
         solver.run()?;
@@ -44,6 +154,71 @@ where
     Ok(None)
 }
 
+/// Same as the `run`, but for the board not wrapped into the `MutRc`.
+///
+/// The board gets all the cells solved for sure, and the found solutions are returned.
+/// The board callbacks are not fired while solving.
+#[cfg(not(feature = "sat"))]
+#[allow(dead_code)]
+pub fn run_ref<B, S, P>(
+    board: &mut Board<B>,
+    max_solutions: Option<usize>,
+    timeout: Option<u32>,
+    max_depth: Option<usize>,
+) -> Result<Vec<Vec<B::Color>>, String>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    let shared = MutRc::new(board.clone());
+    let backtracking = run::<B, S, P>(MutRc::clone(&shared), max_solutions, timeout, max_depth)?;
+
+    let shared = shared.read();
+    board.restore(shared.make_snapshot());
+
+    Ok(match backtracking {
+        Some(backtracking) => backtracking.solutions,
+        None if shared.is_solved_full() => vec![shared.make_snapshot()],
+        None => vec![],
+    })
+}
+
+/// Whether the puzzle has exactly one solution.
+///
+/// The search stops after the second solution is found.
+/// The given board is not changed: the solving is done on its copy.
+/// The contradictory puzzles give the error, as the `run` does.
+#[cfg(not(feature = "sat"))]
+#[allow(clippy::needless_pass_by_value)]
+#[allow(dead_code)]
+pub fn has_unique_solution<B, S, P>(board: MutRc<Board<B>>) -> Result<bool, String>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    let copy = MutRc::new(board.read().clone());
+    let backtracking = run::<B, S, P>(MutRc::clone(&copy), Some(2), None, None)?;
+
+    let solutions = match backtracking {
+        Some(backtracking) if !backtracking.solutions.is_empty() => backtracking.solutions.len(),
+        // solved without the search
+        _ => usize::from(copy.read().is_solved_full()),
+    };
+    Ok(solutions == 1)
+}
+
+/// The number of solutions to enumerate with the SAT solver if the `max_solutions` is not given.
+/// The under-constrained puzzles can have astronomically many solutions.
+#[cfg(feature = "sat")]
+pub const DEFAULT_MAX_SAT_SOLUTIONS: usize = 1000;
+
+/// Solve the puzzle and return the iterator over the solutions
+/// if the board cannot be solved without the SAT solver.
+///
+/// Without the `max_solutions` at most `DEFAULT_MAX_SAT_SOLUTIONS` are found,
+/// use the `Some(usize::MAX)` to enumerate all of them.
 #[cfg(feature = "sat")]
 #[allow(clippy::needless_pass_by_value)]
 pub fn run<B, S, P>(
@@ -55,14 +230,12 @@ where
     S: line::LineSolver<BlockType = B>,
     P: ProbeSolver<BlockType = B>,
 {
-    use crate::board::LineDirection;
+    board.read().check_clues()?;
+    if solve_trivial(&board)? {
+        return Ok(None);
+    }
 
-    warn!("Solving with simple line propagation");
-    let mut solver = propagation::Solver::new(MutRc::clone(&board));
-    let solved_points = solver
-        .run::<S>(None)
-        .map_err(|err| format!("Bad puzzle: simple propagation failed: {:?}", err))?;
-    warn!("Solved {} points", solved_points.len());
+    let _ = propagate::<B, S>(&board, None)?;
 
     if board.read().is_solved_full() {
         return Ok(None);
@@ -88,10 +261,153 @@ where
             board.read().make_snapshot(),
         );
 
-        let solutions_iter = solver.run(impact, max_solutions);
+        let max_solutions = max_solutions.unwrap_or(DEFAULT_MAX_SAT_SOLUTIONS);
+        let solutions_iter = solver.run(impact, Some(max_solutions));
 
         return Ok(Some(solutions_iter));
     }
 
     Ok(None)
 }
+
+/// Whether the puzzle has exactly one solution.
+///
+/// The search stops after the second solution is found.
+/// The given board is not changed: the solving is done on its copy.
+/// The contradictory puzzles give the error, as the `run` does.
+#[cfg(feature = "sat")]
+#[allow(clippy::needless_pass_by_value)]
+#[allow(dead_code)]
+pub fn has_unique_solution<B, S, P>(board: MutRc<Board<B>>) -> Result<bool, String>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    let copy = MutRc::new(board.read().clone());
+    let solutions = match run::<B, S, P>(MutRc::clone(&copy), Some(2))? {
+        Some(solutions) => solutions.count(),
+        // solved without the SAT solver
+        None => usize::from(copy.read().is_solved_full()),
+    };
+    Ok(solutions == 1)
+}
+
+/// Same as the `run`, but for the board not wrapped into the `MutRc`.
+///
+/// The board gets all the cells solved for sure, and the found solutions are returned.
+/// The board callbacks are not fired while solving.
+#[cfg(feature = "sat")]
+#[allow(dead_code)]
+pub fn run_ref<B, S, P>(
+    board: &mut Board<B>,
+    max_solutions: Option<usize>,
+) -> Result<Vec<Vec<B::Color>>, String>
+where
+    B: Block,
+    S: line::LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    let shared = MutRc::new(board.clone());
+    let solutions = run::<B, S, P>(MutRc::clone(&shared), max_solutions)?;
+
+    let shared = shared.read();
+    board.restore(shared.make_snapshot());
+
+    Ok(match solutions {
+        Some(solutions) => solutions.collect(),
+        None if shared.is_solved_full() => vec![shared.make_snapshot()],
+        None => vec![],
+    })
+}
+
+#[allow(dead_code)]
+fn next_number(numbers: &mut impl Iterator<Item = Result<usize, String>>) -> Result<usize, String> {
+    numbers
+        .next()
+        .unwrap_or_else(|| Err("Unexpected end of input".to_string()))
+}
+
+/// The blocks of a single line terminated with the `0`.
+#[allow(dead_code)]
+fn read_spoj_clue(
+    numbers: &mut impl Iterator<Item = Result<usize, String>>,
+) -> Result<Description<BinaryBlock>, String> {
+    let mut blocks = vec![];
+    loop {
+        match next_number(numbers)? {
+            0 => return Ok(Description::new(blocks)),
+            size => blocks.push(BinaryBlock(size)),
+        }
+    }
+}
+
+/// Solve the black-and-white puzzles in the [SPOJ JCROSS](https://www.spoj.com/problems/JCROSS/) format
+/// (the `spoj` example is the thin wrapper around it).
+///
+/// The input starts with the number of puzzles followed by every puzzle:
+/// the line with the height and the width, then the rows and the columns clues,
+/// one line per clue terminated with `0`.
+/// The input is read lazily, so every puzzle is solved as soon as it is read.
+///
+/// The first found solution of every puzzle is written as the lines of `#` and `.`
+/// (nothing is written for the puzzle without a solution).
+/// The malformed input or the too big board stops the processing with the error.
+#[allow(dead_code)]
+pub fn run_spoj_stream(input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+    let numbers = &mut input.lines().flat_map(|line| match line {
+        Ok(line) => line
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|err| format!("Invalid number {:?}: {}", token, err))
+            })
+            .collect(),
+        Err(err) => vec![Err(err.to_string())],
+    });
+
+    let puzzles_number = next_number(numbers)?;
+    for _ in 0..puzzles_number {
+        let height = next_number(numbers)?;
+        let width = next_number(numbers)?;
+        let rows = (0..height)
+            .map(|_| read_spoj_clue(numbers))
+            .collect::<Result<_, _>>()?;
+        let columns = (0..width)
+            .map(|_| read_spoj_clue(numbers))
+            .collect::<Result<_, _>>()?;
+
+        let mut board = Board::try_with_descriptions_and_palette(
+            rows,
+            columns,
+            None,
+            Board::<BinaryBlock>::DEFAULT_MAX_AREA,
+        )?;
+        #[cfg(not(feature = "sat"))]
+        let solutions =
+            run_ref::<_, DynamicSolver<_>, FullProbe1<_>>(&mut board, Some(1), None, None);
+        #[cfg(feature = "sat")]
+        let solutions = run_ref::<_, DynamicSolver<_>, FullProbe1<_>>(&mut board, Some(1));
+
+        let solutions = match solutions {
+            Ok(solutions) => solutions,
+            Err(err) => {
+                warn!("Skipping the puzzle without a solution: {}", err);
+                continue;
+            }
+        };
+
+        if let Some(solution) = solutions.into_iter().next() {
+            for row in solution.chunks(width.max(1)) {
+                let row: String = row
+                    .iter()
+                    .map(|&cell| if cell == BinaryColor::Black { '#' } else { '.' })
+                    .collect();
+                writeln!(output, "{}", row).map_err(|err| err.to_string())?;
+            }
+        }
+    }
+
+    Ok(())
+}
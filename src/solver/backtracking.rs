@@ -1,4 +1,9 @@
-use std::{cmp::Reverse, fmt, marker::PhantomData, time::Instant};
+use std::{
+    cmp::Reverse,
+    fmt,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
 use hashbrown::{HashMap, HashSet};
 use log::{debug, info, log_enabled, warn, Level};
@@ -18,6 +23,47 @@ use crate::{
 
 type Solution<B> = Vec<<B as Block>::Color>;
 
+/// The measurements of the whole `Solver::run`
+/// (including the propagation and probing before the search).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveStats {
+    /// The cells solved either on the board or in the found solutions.
+    pub solved_cells: usize,
+    pub elapsed: Duration,
+}
+
+impl SolveStats {
+    /// Unlike the raw time, the throughput can be compared across the puzzles of different sizes.
+    #[allow(dead_code)]
+    pub fn cells_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.solved_cells as f64 / secs
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// The counters of the work done by the probing and by the search, e.g. for benchmarking.
+/// Unlike the `SolveStats`, they do not depend on the time measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct SearchCounters {
+    /// The cells probed (every color of the cell is tried in a single probe).
+    pub probes: usize,
+    /// The probes that found a color leading to the contradiction.
+    pub contradictions: usize,
+    /// The line solutions found in the cache.
+    pub cache_hits: usize,
+    /// The line solutions not found in the cache (and solved from scratch).
+    pub cache_misses: usize,
+    /// The nodes of the search tree expanded.
+    pub nodes_visited: usize,
+    /// The maximum number of the nested guesses made.
+    pub depth_reached: usize,
+}
+
 #[derive(Debug)]
 pub struct Solver<B, P, S>
 where
@@ -32,21 +78,40 @@ where
     max_solutions: Option<usize>,
     timeout: Option<u32>,
     max_depth: Option<usize>,
+    initial_directions: Vec<(Point, B::Color)>,
+    max_cache_size: Option<usize>,
+    strategy: ChoosePixel,
 
     // dynamic variables
     pub solutions: Vec<Solution<B>>,
+    /// The cells solved for sure when the search was interrupted by the timeout.
+    /// Is `None` if the search completed in time.
+    pub partial_solution: Option<Solution<B>>,
+    timed_out_in_propagation: bool,
+    /// Whether the search stopped on the timeout, not by exploring all the paths.
+    interrupted: bool,
+    first_solution_path: Vec<(Point, B::Color)>,
     depth_reached: usize,
+    guesses_made: usize,
+    nodes_visited: usize,
     start_time: Option<Instant>,
+    stats: Option<SolveStats>,
     //explored_paths: HashSet<Vec<(Point, B::Color)>>,
     pub search_tree: SearchTreeRef<(Point, B::Color), f64>,
 
     _phantom: PhantomData<S>,
 }
 
+/// How to rate the unsolved cell to guess by the number of cells
+/// solved with every of its colors (see the `Solver::with_strategy`).
+///
+/// For details, see <https://ieeexplore.ieee.org/document/6476646>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
-enum ChoosePixel {
+pub enum ChoosePixel {
     Sum,
     Min,
+    /// The most trivial, but also the most ineffective strategy.
     Max,
     Mul,
     Sqrt,
@@ -54,6 +119,13 @@ enum ChoosePixel {
     MinLogd,
 }
 
+#[allow(clippy::derivable_impls)]
+impl Default for ChoosePixel {
+    fn default() -> Self {
+        Self::Sqrt
+    }
+}
+
 type SearchTreeRef<K, V> = MutRc<SearchTree<K, V>>;
 
 pub struct SearchTree<K, V> {
@@ -235,40 +307,134 @@ where
             max_solutions,
             timeout,
             max_depth,
+            initial_directions: vec![],
+            max_cache_size: None,
+            strategy: ChoosePixel::default(),
             solutions: vec![],
+            partial_solution: None,
+            timed_out_in_propagation: false,
+            interrupted: false,
+            first_solution_path: vec![],
             depth_reached: 0,
+            guesses_made: 0,
+            nodes_visited: 0,
             start_time: None,
+            stats: None,
             //explored_paths: HashSet::new(),
             search_tree: MutRc::new(SearchTree::new()),
             _phantom: PhantomData,
         }
     }
 
+    /// Choose the cells to guess with the given strategy instead of the default `ChoosePixel::Sqrt`.
+    #[allow(dead_code)]
+    pub fn with_strategy(mut self, strategy: ChoosePixel) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the (point, color) pairs to try first on the top level of the search,
+    /// e.g. the guesses made by a human. The pairs that are out of the board
+    /// or already impossible when the search starts are silently ignored.
+    #[allow(dead_code)]
+    pub fn set_initial_directions(&mut self, directions: Vec<(Point, B::Color)>) {
+        self.initial_directions = directions;
+    }
+
+    /// Clear the line solutions cache on every board restore
+    /// if it contains more than `size` entries, to bound the memory during deep searches.
+    #[allow(dead_code)]
+    pub fn set_max_cache_size(&mut self, size: usize) {
+        self.max_cache_size = Some(size);
+    }
+
     pub fn run(&mut self) -> Result<(), String> {
+        self.run_timed(None)
+    }
+
+    /// Same as the `run`, but skip the initial probing of all the unsolved cells
+    /// if it was already done by the caller on the current board state
+    /// (e.g. with the `ProbeSolver::run_unsolved`).
+    #[allow(dead_code)]
+    pub fn run_with_impact(&mut self, impact: Impact<B>) -> Result<(), String> {
+        self.run_timed(Some(impact))
+    }
+
+    fn run_timed(&mut self, impact: Option<Impact<B>>) -> Result<(), String> {
+        let run_time = time::now();
+        let result = self.run_search(impact);
+
+        if let Some(run_time) = run_time {
+            let board = self.board();
+            let solved_cells = if self.solutions.is_empty() {
+                board.width() * board.height() - board.unsolved_cells().count()
+            } else {
+                board.width() * board.height()
+            };
+            let stats = SolveStats {
+                solved_cells,
+                elapsed: run_time.elapsed(),
+            };
+            drop(board);
+            self.stats = Some(stats);
+        }
+
+        result
+    }
+
+    /// The throughput of the last `run`. Available only with the `std_time` feature.
+    #[allow(dead_code)]
+    pub fn stats(&self) -> Option<SolveStats> {
+        self.stats
+    }
+
+    /// Put the found solutions into the canonical order (see the `Board::sort_solutions`)
+    /// to make the output reproducible regardless of the search heuristics.
+    pub fn sort_solutions(&mut self) {
+        Board::<B>::sort_solutions(&mut self.solutions);
+    }
+
+    fn run_search(&mut self, impact: Option<Impact<B>>) -> Result<(), String> {
         if self.is_solved() {
             return Ok(());
         }
 
-        let impact = self.probe_solver.run_unsolved::<S>()?;
+        let impact = match impact {
+            Some(impact) => impact,
+            None => self.probe_solver.run_unsolved::<S>()?,
+        };
         if self.is_solved() {
             return Ok(());
         }
 
         self.start_time = time::now();
 
-        let directions = self.choose_directions(impact);
+        let directions = self.with_initial_directions(self.choose_directions(impact));
         warn!(
             "Starting depth-first search (initial rate is {:.4})",
             self.board().solution_rate()
         );
+        self.interrupted = false;
         let success = self.search(directions, &[])?;
+        if self.interrupted {
+            warn!(
+                "Search interrupted by the timeout (solutions found: {}). Saving the partial solution",
+                self.solutions.len()
+            );
+            // the top level changes are not restored, so the board contains only the solved cells
+            let cells = self.board().make_snapshot();
+            self.partial_solution = Some(cells);
+            return Ok(());
+        }
+
         if !success {
             return Err("Backtracking failed".to_string());
         }
 
         warn!(
-            "Search completed (depth reached: {}, solutions found: {})",
+            "Search completed (depth reached: {}, guesses made: {}, solutions found: {})",
             self.depth_reached,
+            self.guesses_made,
             self.solutions.len()
         );
 
@@ -286,6 +452,59 @@ where
         Ok(())
     }
 
+    /// Save the cells solved so far as the partial solution without starting the search,
+    /// since the timeout was already reached by the preceding propagation.
+    pub(crate) fn interrupt_in_propagation(&mut self) {
+        warn!("Propagation interrupted by the timeout. Saving the partial solution");
+        let cells = self.board().make_snapshot();
+        self.partial_solution = Some(cells);
+        self.timed_out_in_propagation = true;
+    }
+
+    /// Whether the timeout was reached even before the search started.
+    /// The `partial_solution` is also set in that case.
+    pub fn timed_out_in_propagation(&self) -> bool {
+        self.timed_out_in_propagation
+    }
+
+    /// Whether at least one guess was made during the search,
+    /// i.e. the puzzle cannot be solved with the logic (propagation and probing) only.
+    pub fn required_guessing(&self) -> bool {
+        self.guesses_made > 0
+    }
+
+    /// The maximum number of the nested guesses made during the search.
+    #[allow(dead_code)]
+    pub fn depth_reached(&self) -> usize {
+        self.depth_reached
+    }
+
+    /// The total number of the guesses made during the search.
+    #[allow(dead_code)]
+    pub fn guesses_made(&self) -> usize {
+        self.guesses_made
+    }
+
+    /// The counters of the probing (including the one before the search)
+    /// and of the search made by the `run`.
+    #[allow(dead_code)]
+    pub fn search_counters(&self) -> SearchCounters {
+        SearchCounters {
+            nodes_visited: self.nodes_visited,
+            depth_reached: self.depth_reached,
+            ..self.probe_solver.stats()
+        }
+    }
+
+    /// The guesses that led to the first found solution in the order they were made.
+    /// The cells solved by the logic in between are not included.
+    ///
+    /// Empty if the puzzle was solved without guessing or no solution was found.
+    #[allow(dead_code)]
+    pub fn solution_path(&self) -> &[(Point, B::Color)] {
+        &self.first_solution_path
+    }
+
     fn board(&self) -> ReadRef<'_, Board<B>> {
         self.board.read()
     }
@@ -320,10 +539,13 @@ where
         false
     }
 
-    fn add_solution(&mut self) {
+    fn add_solution(&mut self, path: &[(Point, B::Color)]) {
         // TODO: force to check the board
         info!("Found one of solutions");
         if !self.already_found() {
+            if self.solutions.is_empty() {
+                self.first_solution_path = path.to_vec();
+            }
             let cells = self.board().make_snapshot();
             self.solutions.push(cells);
         }
@@ -346,7 +568,7 @@ where
             .iter()
             .map(|(point, color_to_impact)| {
                 let values = color_to_impact.values();
-                let priority: Priority = Self::rate_by_impact(values).into();
+                let priority: Priority = self.rate_by_impact(values).into();
                 (point, priority)
             })
             .collect();
@@ -356,9 +578,17 @@ where
         points_rate
             .iter()
             .flat_map(|&(point, _rate)| {
-                let mut point_colors: Vec<_> =
-                    point_wise[point].iter().map(|(&k, &v)| (k, v)).collect();
-                // the most impacting color goes first
+                let color_to_impact = &point_wise[point];
+                // take the colors in the order of the cell variants (not the hash map order)
+                // to break the ties deterministically
+                let mut point_colors: Vec<_> = self
+                    .board()
+                    .cell(point)
+                    .variants()
+                    .into_iter()
+                    .filter_map(|color| color_to_impact.get(&color).map(|&v| (color, v)))
+                    .collect();
+                // the most impacting color goes first (the sort is stable)
                 point_colors.sort_by_key(|(_color, (new_points, _priority))| Reverse(*new_points));
                 point_colors
                     .into_iter()
@@ -367,10 +597,42 @@ where
             .collect()
     }
 
-    const CHOOSE_STRATEGY: ChoosePixel = ChoosePixel::Sqrt;
+    /// Put the valid user-supplied directions before the chosen ones
+    fn with_initial_directions(
+        &self,
+        directions: Vec<(Point, B::Color)>,
+    ) -> Vec<(Point, B::Color)> {
+        let board = self.board();
+        let mut initial: Vec<_> = self
+            .initial_directions
+            .iter()
+            .filter(|(point, color)| {
+                let valid = point.x < board.width()
+                    && point.y < board.height()
+                    && !board.cell(point).is_solved()
+                    && board.cell(point).variants().contains(color);
+                if !valid {
+                    info!(
+                        "Skipping the invalid initial direction {:?}",
+                        (point, color)
+                    );
+                }
+                valid
+            })
+            .copied()
+            .collect();
+        initial.dedup();
+
+        let rest = directions
+            .into_iter()
+            .filter(|direction| !initial.contains(direction))
+            .collect::<Vec<_>>();
+        initial.extend(rest);
+        initial
+    }
 
     #[allow(single_use_lifetimes)]
-    fn rate_by_impact<'a>(impact: impl Iterator<Item = &'a (usize, Priority)>) -> f64 {
+    fn rate_by_impact<'a>(&self, impact: impl Iterator<Item = &'a (usize, Priority)>) -> f64 {
         let sizes_only: Vec<_> = impact.map(|(new_points, _priority)| *new_points).collect();
 
         let min = sizes_only.iter().min().unwrap_or(&0);
@@ -380,9 +642,7 @@ where
         let log = |f: f64| f.ln_1p() + 1.0;
 
         #[allow(clippy::cast_precision_loss)]
-        // Max is the most trivial, but also most ineffective strategy.
-        // For details, see https://ieeexplore.ieee.org/document/6476646
-        match Self::CHOOSE_STRATEGY {
+        match self.strategy {
             ChoosePixel::Sum => sum as f64,
             ChoosePixel::Min => *min as f64,
             ChoosePixel::Max => *max as f64,
@@ -418,12 +678,14 @@ where
             return Ok(true);
         }
 
+        self.nodes_visited += 1;
         let save = self.board().make_snapshot();
         let result = self.search_mutable(directions, path);
 
         // do not restore the solved cells on a root path - they are really solved!
         if !path.is_empty() {
             Board::restore_with_callback(&self.board, save);
+            self.shrink_cache();
             self.set_explored(path);
         }
 
@@ -497,7 +759,7 @@ where
                 // rate = board.solution_rate
                 // self._add_search_result(path, rate)
                 if self.board().is_solved_full() {
-                    self.add_solution();
+                    self.add_solution(path);
                     warn!("The only color {:?} for the {:?} lead to full solution. No need to traverse the path {:?} anymore", color, point, path);
                     return Ok(true);
                 }
@@ -523,10 +785,12 @@ where
                 self.add_search_score(path, rate);
             }
 
+            self.guesses_made += 1;
             let guess_save = self.board().make_snapshot();
             let state_result = self.try_direction(&full_path);
             //let is_solved = board.is_solved_full();
             Board::restore_with_callback(&self.board, guess_save);
+            self.shrink_cache();
             self.set_explored(&full_path);
 
             let success = state_result?;
@@ -568,7 +832,7 @@ where
                 // rate = board.solution_rate
                 // self._add_search_result(path, rate)
                 if self.board().is_solved_full() {
-                    self.add_solution();
+                    self.add_solution(path);
                     warn!(
                         "The negation of color {:?} for the {:?} lead to full solution. No need to traverse the path {:?} anymore.",
                         color, point, path);
@@ -606,6 +870,19 @@ where
         Ok(true)
     }
 
+    fn shrink_cache(&mut self) {
+        if let Some(max_cache_size) = self.max_cache_size {
+            let cache_size = self.probe_solver.cache_size();
+            if cache_size > max_cache_size {
+                info!(
+                    "Clearing the cache of {} line solutions (max is {})",
+                    cache_size, max_cache_size
+                );
+                self.probe_solver.clear_cache();
+            }
+        }
+    }
+
     fn add_search_score(&mut self, path: &[(Point, B::Color)], score: f64) {
         if log_enabled!(Level::Info) {
             SearchTree::add(MutRc::clone(&self.search_tree), path, Some(score));
@@ -628,7 +905,7 @@ where
 
         // add every cell to the jobs queue
         let mut probe_jobs = self.probe_solver.unsolved_cells();
-        let new_jobs = self.set_guess(direction);
+        let new_jobs = self.set_guess(path);
         match new_jobs {
             // update with more prioritized cells
             Ok(new_jobs) => {
@@ -675,8 +952,8 @@ where
         }
     }
 
-    fn set_guess(&mut self, guess: (Point, B::Color)) -> Result<Vec<(Point, Priority)>, String> {
-        let (point, color) = guess;
+    fn set_guess(&mut self, path: &[(Point, B::Color)]) -> Result<Vec<(Point, Priority)>, String> {
+        let (point, color) = *path.last().expect("Path should be non-empty");
 
         if !self.board().cell(&point).variants().contains(&color) {
             info!("The probe is useless: color {:?} already unset", color);
@@ -697,18 +974,27 @@ where
             })?;
 
         if self.board().is_solved_full() {
-            self.add_solution();
+            self.add_solution(path);
             return Ok(vec![]);
         }
 
         Ok(new_probes)
     }
 
+    fn timeout_reached(&self) -> bool {
+        if let Some(timeout) = self.timeout {
+            if let Some(start_time) = self.start_time {
+                return start_time.elapsed().as_secs() >= timeout.into();
+            }
+        }
+        false
+    }
+
     /// Whether we reached the defined limits:
     /// 1) number of solutions found
     /// 2) the maximum allowed run time
     /// 3) the maximum depth
-    fn limits_reached(&self, depth: usize) -> bool {
+    fn limits_reached(&mut self, depth: usize) -> bool {
         if let Some(max_solutions) = self.max_solutions {
             let solutions_number = self.solutions.len();
             if solutions_number >= max_solutions {
@@ -720,17 +1006,15 @@ where
             }
         }
 
-        if let Some(timeout) = self.timeout {
-            if let Some(start_time) = self.start_time {
-                let run_time = start_time.elapsed();
-                if run_time.as_secs() >= timeout.into() {
-                    if depth == 0 {
-                        // only show log on the most top level
-                        warn!("Searched too long: {:.4}s", run_time.as_secs());
-                    }
-                    return true;
+        if self.timeout_reached() {
+            self.interrupted = true;
+            if depth == 0 {
+                // only show log on the most top level
+                if let Some(start_time) = self.start_time {
+                    warn!("Searched too long: {:.4}s", start_time.elapsed().as_secs());
                 }
             }
+            return true;
         }
 
         if let Some(max_depth) = self.max_depth {
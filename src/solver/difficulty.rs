@@ -0,0 +1,121 @@
+//! Estimate how hard the puzzle is for a human solver
+//! by the stage of the solving pipeline that finishes it.
+
+use log::warn;
+
+use crate::{
+    block::Block,
+    board::Board,
+    solver::{backtracking, line::LineSolver, probing::ProbeSolver, propagate},
+    utils::rc::MutRc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(dead_code)]
+pub enum DifficultyTier {
+    /// Solved with the simple line propagation only.
+    Trivial,
+    /// Requires the probing (trying every color of every unsolved cell with the propagation).
+    Easy,
+    /// Requires a shallow search with a few guesses.
+    Medium,
+    /// Requires the deeper search but still within the `DifficultyThresholds`.
+    Hard,
+    /// The search is deeper (or longer) than the `DifficultyThresholds` allow.
+    Extreme,
+}
+
+/// The limits of the search splitting the `Medium`, `Hard` and `Extreme` puzzles.
+///
+/// The depth is the maximum number of the nested guesses
+/// and the guesses are counted across the whole search tree.
+/// The puzzle falls into the tier only if both of its limits are met.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct DifficultyThresholds {
+    pub max_medium_depth: usize,
+    pub max_medium_guesses: usize,
+    pub max_hard_depth: usize,
+    pub max_hard_guesses: usize,
+    /// The search time limit in seconds (requires the `std_time` feature).
+    /// The puzzles that cannot be solved in time are considered `Extreme`.
+    pub timeout: Option<u32>,
+}
+
+impl Default for DifficultyThresholds {
+    fn default() -> Self {
+        Self {
+            max_medium_depth: 2,
+            max_medium_guesses: 10,
+            max_hard_depth: 10,
+            max_hard_guesses: 1000,
+            timeout: None,
+        }
+    }
+}
+
+impl DifficultyThresholds {
+    #[allow(dead_code)]
+    fn tier(&self, depth: usize, guesses: usize) -> DifficultyTier {
+        if depth <= self.max_medium_depth && guesses <= self.max_medium_guesses {
+            DifficultyTier::Medium
+        } else if depth <= self.max_hard_depth && guesses <= self.max_hard_guesses {
+            DifficultyTier::Hard
+        } else {
+            DifficultyTier::Extreme
+        }
+    }
+}
+
+/// Classify the puzzle with the default thresholds.
+/// The given board is not changed.
+#[allow(dead_code)]
+pub fn classify<B, S, P>(board: &Board<B>) -> Result<DifficultyTier, String>
+where
+    B: Block,
+    S: LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    classify_with::<B, S, P>(board, &DifficultyThresholds::default())
+}
+
+/// Run the solving stages one by one and stop on the first one that solves the puzzle.
+///
+/// The search looks for the second solution too, so the ambiguous puzzles
+/// are classified by the effort required to find the first two of their solutions.
+#[allow(dead_code)]
+pub fn classify_with<B, S, P>(
+    board: &Board<B>,
+    thresholds: &DifficultyThresholds,
+) -> Result<DifficultyTier, String>
+where
+    B: Block,
+    S: LineSolver<BlockType = B>,
+    P: ProbeSolver<BlockType = B>,
+{
+    board.check_clues()?;
+    let board = MutRc::new(board.clone());
+
+    let _ = propagate::<B, S>(&board, None)?;
+    if board.read().is_solved_full() {
+        return Ok(DifficultyTier::Trivial);
+    }
+
+    let impact = {
+        let mut probe_solver = P::with_board(MutRc::clone(&board));
+        probe_solver.run_unsolved::<S>()?
+    };
+    if board.read().is_solved_full() {
+        return Ok(DifficultyTier::Easy);
+    }
+
+    let mut solver =
+        backtracking::Solver::<_, P, S>::with_options(board, Some(2), thresholds.timeout, None);
+    solver.run_with_impact(impact)?;
+    if solver.partial_solution.is_some() {
+        warn!("The search is interrupted by the timeout");
+        return Ok(DifficultyTier::Extreme);
+    }
+
+    Ok(thresholds.tier(solver.depth_reached(), solver.guesses_made()))
+}
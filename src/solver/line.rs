@@ -1,4 +1,4 @@
-use std::iter::once;
+use std::{iter::once, mem::size_of};
 
 use crate::{
     block::{
@@ -16,6 +16,13 @@ pub trait LineSolver {
     fn new(desc: ReadRc<Description<Self::BlockType>>, line: Line<LineColor<Self>>) -> Self;
     fn solve(&mut self) -> Result<(), UnsolvableLine>;
     fn into_solution(self) -> Line<LineColor<Self>>;
+
+    /// Whether the line can be solved at all.
+    /// Should give the same answer as the `solve`, but can be implemented much cheaper.
+    #[allow(dead_code)]
+    fn is_feasible(&mut self) -> bool {
+        self.solve().is_ok()
+    }
 }
 
 pub fn solve<L, B>(
@@ -31,6 +38,16 @@ where
     Ok(solver.into_solution())
 }
 
+#[allow(dead_code)]
+pub fn is_feasible<L, B>(desc: ReadRc<Description<B>>, line: Line<B::Color>) -> bool
+where
+    L: LineSolver<BlockType = B>,
+    B: Block,
+{
+    let mut solver = L::new(desc, line);
+    solver.is_feasible()
+}
+
 pub trait DynamicColor: Color
 where
     Self: Sized,
@@ -49,8 +66,10 @@ pub struct DynamicSolver<B: Block, S = <B as Block>::Color> {
     line: Line<S>,
     block_sums: Vec<usize>,
     job_size: usize,
-    solution_matrix: Vec<Option<bool>>,
+    solution_matrix: Vec<bool>,
     solved_line: Box<[S]>,
+    // only check whether the line can be solved, do not collect the solution
+    feasibility_only: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -67,7 +86,7 @@ where
         let block_sums = Self::calc_block_sum(&desc);
 
         let job_size = desc.vec.len() + 1;
-        let solution_matrix = vec![None; job_size * line.len()];
+        let solution_matrix = vec![false; job_size * line.len()];
 
         let solved_line = line.iter().map(DynamicColor::solved_copy).collect();
 
@@ -78,6 +97,7 @@ where
             job_size,
             solution_matrix,
             solved_line,
+            feasibility_only: false,
         }
     }
 
@@ -99,6 +119,11 @@ where
     fn into_solution(self) -> Line<B::Color> {
         self.solved_line.into()
     }
+
+    fn is_feasible(&mut self) -> bool {
+        self.feasibility_only = true;
+        self.try_solve()
+    }
 }
 
 #[allow(clippy::cast_possible_wrap)]
@@ -119,27 +144,26 @@ where
             return true;
         }
 
+        self.fill_matrix();
+
         let (position, block) = (self.line.len() - 1, self.desc.vec.len());
-        self.solve_block(position as isize, block)
+        let can_be_solved = self.get_sol(position as isize, block);
+        if can_be_solved && !self.feasibility_only {
+            self.collect_solution(position, block);
+        }
+        can_be_solved
     }
 
-    fn solve_block(&mut self, position: isize, block: usize) -> bool {
+    fn get_sol(&self, position: isize, block: usize) -> bool {
         if position < 0 {
             // finished placing the last block, exactly at the beginning of the line.
             return block == 0;
         }
 
-        let position = position as usize;
-
-        if let Some(Some(can_be_solved)) =
-            self.solution_matrix.get(position * self.job_size + block)
-        {
-            return *can_be_solved;
-        }
-
-        let can_be_solved = self.fill_matrix(position, block);
-        self.set_sol(position, block, can_be_solved);
-        can_be_solved
+        self.solution_matrix
+            .get(position as usize * self.job_size + block)
+            .copied()
+            .unwrap_or(false)
     }
 
     fn set_sol(&mut self, position: usize, block: usize, can_be_solved: bool) {
@@ -147,10 +171,11 @@ where
             .solution_matrix
             .get_mut(position * self.job_size + block)
         {
-            *item = Some(can_be_solved);
+            *item = can_be_solved;
         }
     }
 
+    #[allow(clippy::unnecessary_map_or)]
     fn can_be_blank_at(&self, position: usize) -> bool {
         self.line
             .get(position)
@@ -158,76 +183,119 @@ where
     }
 
     fn update_solved(&mut self, position: usize, color: B::Color) {
+        if self.feasibility_only {
+            return;
+        }
+
         if let Some(updated) = self.solved_line.get_mut(position) {
             let current: B::Color = *updated;
             *updated = current.add_color(color);
         }
     }
 
-    fn fill_matrix(&mut self, position: usize, block: usize) -> bool {
+    /// Find for every cell and block whether the first `position + 1` cells
+    /// can hold the first `block` blocks.
+    ///
+    /// Every cell depends only on the cells with the smaller positions,
+    /// so the table is filled from the beginning of the line
+    /// without the recursion (which overflows the stack on the very long lines).
+    fn fill_matrix(&mut self) {
+        for position in 0..self.line.len() {
+            for block in 0..self.job_size {
+                let can_be_solved = self.can_fit(position, block)
+                    && (self.fill_blank(position, block)
+                        || self.fill_color(position, block).is_some());
+                self.set_sol(position, block, can_be_solved);
+            }
+        }
+    }
+
+    fn can_fit(&self, position: usize, block: usize) -> bool {
         // too many blocks left to fit this line segment
         if let Some(sum_up_to) = self.block_sums.get(block) {
             if position < *sum_up_to {
                 return false;
             }
         }
-
-        // do not short-circuit
-        self.fill_matrix_blank(position, block) | self.fill_matrix_color(position, block)
+        true
     }
 
-    fn fill_matrix_blank(&mut self, position: usize, block: usize) -> bool {
-        if self.can_be_blank_at(position) {
-            // current cell is either blank or unknown
-            let has_blank = self.solve_block(position as isize - 1, block);
-            if has_blank {
-                let blank = B::Color::blank();
-                // set cell blank and continue
-                self.update_solved(position, blank);
-                return true;
+    /// Walk back from the last cell through all the placements leading to the solution
+    /// and collect the possible colors of the cells.
+    fn collect_solution(&mut self, last_position: usize, last_block: usize) {
+        let mut reachable = vec![false; self.solution_matrix.len()];
+        if let Some(item) = reachable.get_mut(last_position * self.job_size + last_block) {
+            *item = true;
+        }
+
+        for position in (0..=last_position).rev() {
+            for block in 0..self.job_size {
+                if !reachable[position * self.job_size + block]
+                    || !self.get_sol(position as isize, block)
+                {
+                    continue;
+                }
+
+                if self.fill_blank(position, block) {
+                    // set cell blank and continue
+                    self.update_solved(position, B::Color::blank());
+                    if position > 0 {
+                        reachable[(position - 1) * self.job_size + block] = true;
+                    }
+                }
+
+                if let Some((block_start, color, trailing_space)) = self.fill_color(position, block)
+                {
+                    // set cell blank, place the current block and continue
+                    self.set_color_block(block_start, position, color, trailing_space);
+                    if block_start > 0 {
+                        reachable[(block_start - 1) * self.job_size + block - 1] = true;
+                    }
+                }
             }
         }
+    }
 
-        false
+    fn fill_blank(&self, position: usize, block: usize) -> bool {
+        // current cell is either blank or unknown
+        self.can_be_blank_at(position) && self.get_sol(position as isize - 1, block)
     }
 
-    fn fill_matrix_color(&mut self, position: usize, block: usize) -> bool {
+    /// The start of the current block placed right before the `position`
+    /// (with its color and whether the `position` is the trailing space),
+    /// if the rest of the blocks fit before it.
+    fn fill_color(&self, position: usize, block: usize) -> Option<(usize, B::Color, bool)> {
         // block == 0 means we finished filling all the blocks (can still fill whitespace)
         if block == 0 {
-            return false;
+            return None;
         }
-        if let Some(current_block) = self.desc.vec.get(block - 1) {
-            let mut block_size = current_block.size();
-            let current_color = current_block.color();
-            let should_have_trailing_space = self.trail_with_space(block);
-            if should_have_trailing_space {
-                block_size += 1;
-            }
 
-            let block_start = position as isize - block_size as isize + 1;
+        let current_block = self.desc.vec.get(block - 1)?;
+        let mut block_size = current_block.size();
+        let current_color = current_block.color();
+        let should_have_trailing_space = self.trail_with_space(block);
+        if should_have_trailing_space {
+            block_size += 1;
+        }
 
-            // (position-block_size, position]
-            if self.can_place_color(
-                block_start,
-                position,
+        let block_start = position as isize - block_size as isize + 1;
+
+        // (position-block_size, position]
+        if self.can_place_color(
+            block_start,
+            position,
+            current_color,
+            should_have_trailing_space,
+        ) && self.get_sol(block_start - 1, block - 1)
+        {
+            return Some((
+                block_start as usize,
                 current_color,
                 should_have_trailing_space,
-            ) {
-                let has_color = self.solve_block(block_start - 1, block - 1);
-                if has_color {
-                    // set cell blank, place the current block and continue
-                    self.set_color_block(
-                        block_start as usize,
-                        position,
-                        current_color,
-                        should_have_trailing_space,
-                    );
-                    return true;
-                }
-            }
+            ));
         }
 
-        false
+        None
     }
 
     fn trail_with_space(&self, block: usize) -> bool {
@@ -291,6 +359,268 @@ where
     }
 }
 
+/// Solves the lines with the blocks of a single color (e.g. any black-and-white line)
+/// keeping the possible states of all the cells as the bit masks.
+///
+/// For every block and every cell the block can start from, the union of all the
+/// valid placements of the rest of the blocks is calculated once, so the line
+/// can be no longer than the `MAX_LENGTH` cells (see the `is_suitable`).
+#[derive(Debug)]
+pub struct BitsetSolver<B: Block> {
+    desc: ReadRc<Description<B>>,
+    line: Line<B::Color>,
+    // the cells which can be blank and which can have the blocks color
+    can_be_blank: u64,
+    can_be_colored: u64,
+    // for every (block, start) pair: the (colored, blank) cells
+    // in any of the valid placements of the rest of the line
+    memo: Vec<Option<Option<(u64, u64)>>>,
+    solved_line: Option<(u64, u64)>,
+    feasibility_only: bool,
+}
+
+impl<B> BitsetSolver<B>
+where
+    B: Block,
+    B::Color: DynamicColor,
+{
+    /// Every cell is a bit of the `u64` mask.
+    // the `u64::BITS` requires Rust 1.53
+    #[allow(clippy::manual_bits)]
+    pub const MAX_LENGTH: usize = 8 * size_of::<u64>();
+
+    /// Whether the line is short enough and all the blocks have the same color.
+    pub fn is_suitable(desc: &Description<B>, line: &[B::Color]) -> bool {
+        line.len() <= Self::MAX_LENGTH
+            && desc
+                .vec
+                .iter()
+                .all(|block| block.color() == desc.vec[0].color())
+    }
+
+    fn color(&self) -> Option<B::Color> {
+        self.desc.vec.first().map(|block| block.color())
+    }
+
+    /// The cells in the `[start, end)` range.
+    fn range_mask(start: usize, end: usize) -> u64 {
+        if start >= end {
+            return 0;
+        }
+        (u64::MAX >> (Self::MAX_LENGTH - (end - start))) << start
+    }
+
+    fn solve_block(&mut self, block: usize, start: usize) -> Option<(u64, u64)> {
+        let index = block * (self.line.len() + 1) + start;
+        if let Some(solved) = self.memo[index] {
+            return solved;
+        }
+
+        let solved = self.fill_block(block, start);
+        self.memo[index] = Some(solved);
+        solved
+    }
+
+    /// Place the `block` and the rest of the blocks into the `[start, line_length)`
+    fn fill_block(&mut self, block: usize, start: usize) -> Option<(u64, u64)> {
+        let length = self.line.len();
+        let size = match self.desc.vec.get(block) {
+            Some(current_block) => current_block.size(),
+            None => {
+                // no more blocks: the rest of the line should be blank
+                let rest = Self::range_mask(start, length);
+                if rest & !self.can_be_blank == 0 {
+                    return Some((0, rest));
+                }
+                return None;
+            }
+        };
+
+        let last_start = length.checked_sub(size)?;
+        let mut found: Option<(u64, u64)> = None;
+        for block_start in start..=last_start {
+            let gap = Self::range_mask(start, block_start);
+            if gap & !self.can_be_blank != 0 {
+                // the larger gaps include the same non-blank cell
+                break;
+            }
+
+            let block_end = block_start + size;
+            let body = Self::range_mask(block_start, block_end);
+            if body & !self.can_be_colored != 0 {
+                continue;
+            }
+
+            // the cell after the block (if any) separates it from the next one
+            let (separator, next_start) = if block_end < length {
+                (1 << block_end, block_end + 1)
+            } else {
+                (0, length)
+            };
+            if separator & !self.can_be_blank != 0 {
+                continue;
+            }
+
+            if let Some((colored, blank)) = self.solve_block(block + 1, next_start) {
+                let (all_colored, all_blank) = found.unwrap_or((0, 0));
+                found = Some((
+                    all_colored | body | colored,
+                    all_blank | gap | separator | blank,
+                ));
+                if self.feasibility_only {
+                    break;
+                }
+            }
+        }
+
+        found
+    }
+
+    fn try_solve(&mut self) -> bool {
+        if self.line.is_empty() {
+            // the same as for the `DynamicSolver`
+            self.solved_line = Some((0, 0));
+            return true;
+        }
+
+        self.solved_line = self.solve_block(0, 0);
+        self.solved_line.is_some()
+    }
+}
+
+impl<B> LineSolver for BitsetSolver<B>
+where
+    B: Block,
+    B::Color: DynamicColor,
+{
+    type BlockType = B;
+
+    fn new(desc: ReadRc<Description<B>>, line: Line<B::Color>) -> Self {
+        assert!(
+            Self::is_suitable(&desc, &line),
+            "The line is not suitable for the bitset solver"
+        );
+
+        let color = desc.vec.first().map(|block| block.color());
+        let (mut can_be_blank, mut can_be_colored) = (0, 0);
+        for (i, cell) in line.iter().enumerate() {
+            if cell.can_be_blank() {
+                can_be_blank |= 1 << i;
+            }
+            if let Some(color) = color {
+                if cell.can_be(color) {
+                    can_be_colored |= 1 << i;
+                }
+            }
+        }
+
+        let memo = vec![None; (desc.vec.len() + 1) * (line.len() + 1)];
+        Self {
+            desc,
+            line,
+            can_be_blank,
+            can_be_colored,
+            memo,
+            solved_line: None,
+            feasibility_only: false,
+        }
+    }
+
+    fn solve(&mut self) -> Result<(), UnsolvableLine> {
+        if self.try_solve() {
+            Ok(())
+        } else {
+            Err(UnsolvableLine)
+        }
+    }
+
+    fn into_solution(self) -> Line<B::Color> {
+        let (colored, blank) = self.solved_line.unwrap_or((0, 0));
+        let color = self.color();
+        let both = B::Color::both_colors();
+
+        self.line
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let mut solved = cell.solved_copy();
+                if blank & (1 << i) != 0 {
+                    solved = solved.add_color(B::Color::blank());
+                }
+                if let Some(color) = color {
+                    if colored & (1 << i) != 0 {
+                        solved = solved.add_color(color);
+                    }
+                }
+
+                if Some(solved) == both {
+                    B::Color::default()
+                } else {
+                    solved
+                }
+            })
+            .collect()
+    }
+
+    fn is_feasible(&mut self) -> bool {
+        self.feasibility_only = true;
+        self.try_solve()
+    }
+}
+
+/// Chooses the fastest solver for every line: the `BitsetSolver`
+/// for the short lines with the blocks of a single color
+/// and the `DynamicSolver` for all the others.
+/// The solutions are always the same as the `DynamicSolver` gives.
+///
+/// Exported from the crate root as the `HybridLineSolver`.
+#[derive(Debug)]
+pub struct HybridSolver<B: Block>(HybridInner<B>);
+
+#[derive(Debug)]
+enum HybridInner<B: Block> {
+    Bitset(BitsetSolver<B>),
+    Dynamic(DynamicSolver<B>),
+}
+
+impl<B> LineSolver for HybridSolver<B>
+where
+    B: Block,
+    B::Color: DynamicColor,
+{
+    type BlockType = B;
+
+    fn new(desc: ReadRc<Description<B>>, line: Line<B::Color>) -> Self {
+        let inner = if BitsetSolver::is_suitable(&desc, &line) {
+            HybridInner::Bitset(BitsetSolver::new(desc, line))
+        } else {
+            HybridInner::Dynamic(DynamicSolver::new(desc, line))
+        };
+        Self(inner)
+    }
+
+    fn solve(&mut self) -> Result<(), UnsolvableLine> {
+        match &mut self.0 {
+            HybridInner::Bitset(solver) => solver.solve(),
+            HybridInner::Dynamic(solver) => solver.solve(),
+        }
+    }
+
+    fn into_solution(self) -> Line<B::Color> {
+        match self.0 {
+            HybridInner::Bitset(solver) => solver.into_solution(),
+            HybridInner::Dynamic(solver) => solver.into_solution(),
+        }
+    }
+
+    fn is_feasible(&mut self) -> bool {
+        match &mut self.0 {
+            HybridInner::Bitset(solver) => solver.is_feasible(),
+            HybridInner::Dynamic(solver) => solver.is_feasible(),
+        }
+    }
+}
+
 impl DynamicColor for BinaryColor {
     fn both_colors() -> Option<Self> {
         Some(Self::BlackOrWhite)
@@ -341,9 +671,12 @@ impl DynamicColor for MultiColor {
 
 #[cfg(test)]
 mod tests {
-    use crate::block::binary::{
-        BinaryBlock,
-        BinaryColor::{Black, Undefined, White},
+    use crate::block::{
+        base::clues_from_solution,
+        binary::{
+            BinaryBlock,
+            BinaryColor::{Black, Undefined, White},
+        },
     };
 
     use super::*;
@@ -454,6 +787,109 @@ mod tests {
             assert_eq!(ds.into_solution(), expected.into());
         }
     }
+
+    /// The recursive solver overflowed the stack on such a line
+    #[test]
+    fn solve_very_long_line() {
+        let size = 100_000;
+        let desc = ReadRc::new(Description::new(vec![BinaryBlock(1), BinaryBlock(1)]));
+        let mut line = vec![Undefined; size];
+        line[0] = Black;
+        line[size - 1] = Black;
+
+        let solved = solve::<DynamicSolver<_>, _>(desc, line.into()).unwrap();
+        let mut expected = vec![White; size];
+        expected[0] = Black;
+        expected[size - 1] = Black;
+        assert_eq!(solved, expected.into());
+    }
+
+    /// 1000 blocks of 3 cells, every block can be shifted by one cell
+    #[test]
+    fn solve_many_blocks() {
+        let desc = ReadRc::new(Description::new(vec![BinaryBlock(3); 1000]));
+        let line = vec![Undefined; 4000].into();
+
+        let solved = solve::<DynamicSolver<_>, _>(desc, line).unwrap();
+        let expected: Vec<_> = (0..4000)
+            .map(|i| match i % 4 {
+                1 | 2 => Black,
+                _ => Undefined,
+            })
+            .collect();
+        assert_eq!(solved, expected.into());
+    }
+
+    #[test]
+    fn feasible_same_as_solve() {
+        let (b, w, u) = (Black, White, Undefined);
+
+        let mut lines: Vec<_> = cases()
+            .into_iter()
+            .map(|(desc, line, _expected)| (desc, line))
+            .collect();
+        lines.extend(vec![
+            (vec![3], vec![u, w, u]),
+            (vec![1, 1], vec![b, b, u]),
+            (vec![], vec![u, b]),
+            (vec![2], vec![w, w]),
+        ]);
+
+        for (desc, line) in lines {
+            let as_blocks = desc.iter().map(|b| BinaryBlock(*b)).collect();
+            let desc = ReadRc::new(Description::new(as_blocks));
+            let line: Line<_> = line.into();
+
+            let solved = solve::<DynamicSolver<_>, _>(ReadRc::clone(&desc), ReadRc::clone(&line));
+            let feasible = is_feasible::<DynamicSolver<_>, _>(desc, line);
+            assert_eq!(feasible, solved.is_ok());
+        }
+    }
+
+    /// Every line up to 6 cells with every description fitting it
+    #[test]
+    fn hybrid_same_as_dynamic() {
+        let states = [Undefined, White, Black];
+        for size in 0..=6_u32 {
+            let descriptions: Vec<_> = (0..1 << size)
+                .map(|solution: u32| {
+                    let solution: Vec<_> = (0..size).map(|i| (solution >> i) & 1).collect();
+                    let (_, rows) = clues_from_solution::<BinaryBlock>(&[solution], 0);
+                    ReadRc::new(
+                        rows.into_iter()
+                            .next()
+                            .unwrap_or_else(|| Description::new(vec![])),
+                    )
+                })
+                .collect();
+
+            for line in 0..3_usize.pow(size) {
+                let line: Line<_> = (0..size)
+                    .map(|i| states[line / 3_usize.pow(i) % 3])
+                    .collect();
+
+                for desc in &descriptions {
+                    let dynamic =
+                        solve::<DynamicSolver<_>, _>(ReadRc::clone(desc), ReadRc::clone(&line));
+                    let hybrid =
+                        solve::<HybridSolver<_>, _>(ReadRc::clone(desc), ReadRc::clone(&line));
+                    assert_eq!(
+                        dynamic.as_ref().ok(),
+                        hybrid.as_ref().ok(),
+                        "{:?} {:?}",
+                        desc,
+                        line
+                    );
+
+                    let feasible = is_feasible::<HybridSolver<_>, _>(
+                        ReadRc::clone(desc),
+                        ReadRc::clone(&line),
+                    );
+                    assert_eq!(feasible, dynamic.is_ok());
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -598,4 +1034,97 @@ mod tests_solve_color {
         let mut ds = DynamicSolver::new(desc, unsolved_line(4));
         assert!(ds.solve().is_err());
     }
+
+    fn check_unsolvable(desc: &[ColoredBlock], initial: &[MultiColor]) {
+        let desc = desc_from_slice(desc);
+        assert!(solve::<DynamicSolver<_>, _>(desc, initial.into()).is_err());
+    }
+
+    #[test]
+    fn hybrid_single_color() {
+        let desc = desc_from_slice(&[ColoredBlock::from_size_and_color(2, 4); 2]);
+        let mut line = unsolved_line(7).to_vec();
+        line[1] = MultiColor(4);
+        line[5] = MultiColor(w() + 8);
+
+        let dynamic = solve::<DynamicSolver<_>, _>(ReadRc::clone(&desc), line.clone().into());
+        let hybrid = solve::<HybridSolver<_>, _>(desc, line.into());
+        assert_eq!(hybrid.unwrap(), dynamic.unwrap());
+    }
+
+    #[test]
+    fn bitset_max_length() {
+        let desc = desc_from_slice(&[ColoredBlock::from_size_and_color(2, 4); 2]);
+        let max_length = BitsetSolver::<ColoredBlock>::MAX_LENGTH;
+        assert_eq!(max_length, 64);
+
+        let line = vec![MultiColor(w() + 4); max_length];
+        assert!(BitsetSolver::is_suitable(&desc, &line));
+
+        // the longer line is solved by the dynamic solver
+        let line = vec![MultiColor(w() + 4); max_length + 1];
+        assert!(!BitsetSolver::is_suitable(&desc, &line));
+        assert!(solve::<HybridSolver<_>, _>(desc, line.into()).is_ok());
+    }
+
+    #[test]
+    fn three_same_color() {
+        let desc = [ColoredBlock::from_size_and_color(1, 4); 3];
+        check_solve(&desc, &unsolved_line(5), &[4, w(), 4, w(), 4]);
+        // no space for the gaps
+        check_unsolvable(&desc, &unsolved_line(4));
+    }
+
+    #[test]
+    fn same_color_around_other() {
+        let desc = [
+            ColoredBlock::from_size_and_color(1, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+            ColoredBlock::from_size_and_color(1, 4),
+        ];
+        // no gaps required between the different colors
+        check_solve(&desc, &unsolved_line(3), &[4, 8, 4]);
+        check_solve(
+            &desc,
+            &unsolved_line(4),
+            &[4 + w(), 4 + 8 + w(), 4 + 8 + w(), 4 + w()],
+        );
+    }
+
+    #[test]
+    fn long_with_forbidden_gap() {
+        let desc = [
+            ColoredBlock::from_size_and_color(2, 4),
+            ColoredBlock::from_size_and_color(1, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+        ];
+        // the only placement is [4, 4, w, 4, 8]
+        let mut line = unsolved_line(5).to_vec();
+        line[3] = MultiColor(8);
+        check_unsolvable(&desc, &line);
+    }
+
+    #[test]
+    fn pairs_of_same_colors() {
+        let desc = [
+            ColoredBlock::from_size_and_color(1, 4),
+            ColoredBlock::from_size_and_color(1, 4),
+            ColoredBlock::from_size_and_color(1, 8),
+            ColoredBlock::from_size_and_color(1, 8),
+        ];
+        check_solve(&desc, &unsolved_line(6), &[4, w(), 4, 8, w(), 8]);
+        check_solve(
+            &desc,
+            &unsolved_line(7),
+            &[
+                4 + w(),
+                4 + w(),
+                4 + w(),
+                4 + 8 + w(),
+                8 + w(),
+                8 + w(),
+                8 + w(),
+            ],
+        );
+    }
 }
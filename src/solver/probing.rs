@@ -18,7 +18,10 @@ use crate::{
     },
 };
 
-#[derive(Debug)]
+#[cfg(not(feature = "sat"))]
+use crate::solver::backtracking::SearchCounters;
+
+#[derive(Debug, Clone)]
 pub struct ProbeImpact<C: Color> {
     point: Point,
     color: C,
@@ -27,6 +30,29 @@ pub struct ProbeImpact<C: Color> {
 }
 
 impl<C: Color> ProbeImpact<C> {
+    /// The probed cell
+    #[allow(dead_code)]
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// The probed color
+    #[allow(dead_code)]
+    pub fn color(&self) -> C {
+        self.color
+    }
+
+    /// The cells (with their new colors) solved by setting the probed color
+    #[allow(dead_code)]
+    pub fn cells_solved(&self) -> &[(Point, C)] {
+        &self.cells_solved
+    }
+
+    #[allow(dead_code)]
+    pub fn priority(&self) -> Priority {
+        self.probe_priority
+    }
+
     pub fn into_tuple(self) -> (Point, C, Vec<(Point, C)>, Priority) {
         (
             self.point,
@@ -56,6 +82,7 @@ impl From<f64> for Priority {
     }
 }
 
+type ColorImpact<C> = Result<Vec<(Point, C)>, UnsolvableLine>;
 type PointImpact<C> = Vec<(C, ProbeResult<Vec<(Point, C)>>)>;
 pub type Impact<B> = Vec<ProbeImpact<<B as Block>::Color>>;
 type OrderedPoints = PQ<Point, Priority, DefaultHashBuilder>;
@@ -83,6 +110,23 @@ pub trait ProbeSolver {
     fn run<S>(&mut self, probes: &mut OrderedPoints) -> Result<Impact<Self::BlockType>, String>
     where
         S: LineSolver<BlockType = Self::BlockType>;
+
+    /// The number of memoized line solutions
+    #[allow(dead_code)]
+    fn cache_size(&self) -> usize {
+        0
+    }
+
+    /// Drop the memoized line solutions
+    #[allow(dead_code)]
+    fn clear_cache(&mut self) {}
+
+    /// The counters accumulated by all the runs (the search ones are left empty)
+    #[cfg(not(feature = "sat"))]
+    #[allow(dead_code)]
+    fn stats(&self) -> SearchCounters {
+        SearchCounters::default()
+    }
 }
 
 #[derive(Debug)]
@@ -92,9 +136,36 @@ where
 {
     board: MutRc<Board<B>>,
     low_threshold: Priority,
+    max_probes: Option<usize>,
     propagation_solver: propagation::Solver<B>,
+    probes: usize,
+    contradictions: usize,
+    #[cfg(feature = "threaded")]
+    threads: usize,
+    #[cfg(feature = "threaded")]
+    workers: Vec<ProbeWorker<B>>,
 }
 
+/// The board copy probed by a single thread.
+///
+/// The workers live as long as the probe solver, so the copies are only
+/// synchronized with the board before every batch of probes instead of cloning.
+#[cfg(feature = "threaded")]
+#[derive(Debug)]
+struct ProbeWorker<B>
+where
+    B: Block,
+{
+    board: MutRc<Board<B>>,
+    propagation_solver: propagation::Solver<B>,
+}
+
+/// The number of cells probed by every thread in a single batch.
+/// The larger batches spawn the threads less frequently,
+/// but more probes are wasted when a contradiction is found.
+#[cfg(feature = "threaded")]
+const PROBES_PER_THREAD: usize = 8;
+
 fn low_priority_threshold() -> Priority {
     env::var("LOW_PRIORITY")
         .ok()
@@ -103,6 +174,14 @@ fn low_priority_threshold() -> Priority {
         .into()
 }
 
+#[cfg(feature = "threaded")]
+fn probe_threads() -> usize {
+    env::var("PROBE_THREADS")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(4)
+}
+
 impl<B> ProbeSolver for FullProbe1<B>
 where
     B: Block,
@@ -114,29 +193,52 @@ where
         Self {
             board,
             low_threshold: low_priority_threshold(),
+            max_probes: None,
             propagation_solver,
+            probes: 0,
+            contradictions: 0,
+            #[cfg(feature = "threaded")]
+            threads: probe_threads(),
+            #[cfg(feature = "threaded")]
+            workers: Vec::new(),
         }
     }
 
-    fn unsolved_cells(&self) -> OrderedPoints {
-        let board = self.board();
-        let unsolved = board.unsolved_cells();
+    fn cache_size(&self) -> usize {
+        self.propagation_solver.cache_size()
+    }
 
-        let mut row_rate_cache = Vec::with_none(board.height());
-        let mut column_rate_cache = Vec::with_none(board.width());
+    fn clear_cache(&mut self) {
+        self.propagation_solver.clear_cache();
+    }
 
+    #[cfg(not(feature = "sat"))]
+    fn stats(&self) -> SearchCounters {
+        let (cache_hits, cache_misses) = self.propagation_solver.cache_hits_and_misses();
+        SearchCounters {
+            probes: self.probes,
+            contradictions: self.contradictions,
+            cache_hits,
+            cache_misses,
+            ..SearchCounters::default()
+        }
+    }
+
+    fn unsolved_cells(&self) -> OrderedPoints {
         let mut queue = OrderedPoints::with_default_hasher();
-        queue.extend(unsolved.map(|point| {
-            let no_solved = 4 - board.unsolved_neighbours(&point).count();
-            let row_rate =
-                row_rate_cache.unwrap_or_insert_with(point.y, || board.row_solution_rate(point.y));
-            let column_rate = column_rate_cache
-                .unwrap_or_insert_with(point.x, || board.column_solution_rate(point.x));
-
-            #[allow(clippy::cast_precision_loss)]
-            let priority = no_solved as f64 + row_rate + column_rate;
-            (point, priority.into())
-        }));
+        queue.extend(
+            self.cell_priorities()
+                .into_iter()
+                .map(|(point, priority)| (point, priority.into())),
+        );
+
+        if let Some(max_probes) = self.max_probes {
+            if queue.len() > max_probes {
+                let mut top_queue = OrderedPoints::with_default_hasher();
+                top_queue.extend((0..max_probes).filter_map(|_| queue.pop()));
+                return top_queue;
+            }
+        }
 
         queue
     }
@@ -173,18 +275,33 @@ where
     {
         let mut contradictions_number = 0;
 
-        let impact =
-            loop {
-                let mut impact = Vec::new();
+        let impact = loop {
+            let mut impact = Vec::new();
 
-                if self.is_solved() {
-                    break impact;
+            if self.is_solved() {
+                break impact;
+            }
+
+            let mut false_probes = Vec::new();
+            let mut probe_counter = 0_u32;
+
+            while false_probes.is_empty() {
+                let batch: Vec<_> = (0..self.batch_size())
+                    .filter_map(|_| probes.pop())
+                    .collect();
+                if batch.is_empty() {
+                    break;
                 }
 
-                let mut false_probes = None;
-                let mut probe_counter = 0_u32;
+                let to_probe: Vec<_> = batch
+                    .iter()
+                    .filter(|(_point, priority)| *priority >= self.low_threshold)
+                    .map(|(point, _priority)| *point)
+                    .collect();
+                self.probes += to_probe.len();
+                let mut batch_results = self.probe_points::<S>(&to_probe).into_iter();
 
-                while let Some((point, priority)) = probes.pop() {
+                for (point, priority) in batch {
                     probe_counter += 1;
 
                     debug!(
@@ -203,18 +320,21 @@ where
                         continue;
                     }
 
-                    let probe_results = self.probe::<S>(point);
+                    let probe_results = batch_results
+                        .next()
+                        .expect("Every cell of the batch should be probed");
                     let (contradictions, non_contradictions): (Vec<_>, Vec<_>) = probe_results
                         .into_iter()
                         .partition(|(_color, res)| res.is_contradiction());
 
                     if !contradictions.is_empty() {
-                        let bad_colors = contradictions
+                        let bad_colors: Vec<_> = contradictions
                             .into_iter()
-                            .map(|(color, _should_be_none)| color);
+                            .map(|(color, _should_be_none)| color)
+                            .collect();
 
-                        false_probes = Some((point, bad_colors));
-                        break;
+                        false_probes.push((point, bad_colors));
+                        continue;
                     }
 
                     impact.extend(non_contradictions.into_iter().map(|(color, updated)| {
@@ -226,24 +346,40 @@ where
                         }
                     }));
                 }
+            }
+
+            if false_probes.is_empty() {
+                break impact;
+            }
+
+            // the color leading to the contradiction stays the bad one on the more solved board,
+            // so all the contradictions of the batch are valid
+            for (contradiction, colors) in false_probes {
+                contradictions_number += 1;
+                self.contradictions += 1;
+
+                let variants = self.board().cell(&contradiction).variants();
+                let colors: Vec<_> = colors
+                    .into_iter()
+                    .filter(|color| variants.contains(color))
+                    .collect();
+                // already solved by the previous contradiction of the batch
+                if colors.is_empty() {
+                    continue;
+                }
 
-                if let Some((contradiction, colors)) = false_probes {
-                    contradictions_number += 1;
-
-                    for color in colors {
-                        Board::unset_color_with_callback(&self.board, &contradiction, &color)?;
-                    }
-                    let new_probes = self.propagate_point::<S>(&contradiction).map_err(|err| {
-                        format!(
-                            "Error while propagating contradicted values in {:?}: {:?}",
-                            contradiction, err
-                        )
-                    })?;
-                    probes.extend(new_probes);
-                } else {
-                    break impact;
+                for color in colors {
+                    Board::unset_color_with_callback(&self.board, &contradiction, &color)?;
                 }
-            };
+                let new_probes = self.propagate_point::<S>(&contradiction).map_err(|err| {
+                    format!(
+                        "Error while propagating contradicted values in {:?}: {:?}",
+                        contradiction, err
+                    )
+                })?;
+                probes.extend(new_probes);
+            }
+        };
 
         if contradictions_number > 0 {
             info!("Contradictions found: {}", contradictions_number);
@@ -278,6 +414,123 @@ impl<PropagationResult> ProbeResult<PropagationResult> {
     }
 }
 
+#[allow(dead_code)]
+impl<B> FullProbe1<B>
+where
+    B: Block,
+{
+    /// Probe only the given number of the most prioritized cells
+    /// on every iteration instead of all the unsolved ones.
+    ///
+    /// It makes the probing faster on large boards,
+    /// but can leave more work for the backtracking.
+    pub fn with_max_probes(mut self, max_probes: usize) -> Self {
+        self.max_probes = Some(max_probes);
+        self
+    }
+
+    /// Memoize at most `capacity` line solutions for the whole board
+    /// to bound the memory on constrained targets.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.propagation_solver =
+            propagation::Solver::with_cache_capacity(MutRc::clone(&self.board), capacity);
+        self
+    }
+
+    /// Reuse the line solutions cache owned by the caller
+    /// (e.g. shared between many similar puzzles).
+    pub fn with_shared_cache(mut self, cache: propagation::SharedCache<B>) -> Self {
+        self.propagation_solver =
+            propagation::Solver::with_shared_cache(MutRc::clone(&self.board), cache);
+        self
+    }
+
+    /// Probe the cells with the given number of threads
+    /// (by default it is taken from the `PROBE_THREADS` environment variable or 4).
+    ///
+    /// With a single thread the cells are probed on the board itself.
+    #[cfg(feature = "threaded")]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self.workers.clear();
+        self
+    }
+}
+
+/// Set the color for the cell, propagate it and restore the board back.
+///
+/// Return the newly solved cells (with their colors) or the contradiction.
+fn probe_color<B, S>(
+    board: &MutRc<Board<B>>,
+    propagation_solver: &mut propagation::Solver<B>,
+    point: Point,
+    color: B::Color,
+) -> ColorImpact<B::Color>
+where
+    B: Block,
+    S: LineSolver<BlockType = B>,
+{
+    let save = board.read().make_snapshot();
+    Board::set_color_with_callback(board, &point, &color);
+
+    let solved = propagation_solver.run_probe::<S>(point).map(|new_cells| {
+        // get the color of every changed cell before restoring
+        let board = board.read();
+        new_cells
+            .into_iter()
+            .map(|point| (point, board.cell(&point)))
+            .collect()
+    });
+    Board::restore_with_callback(board, save);
+
+    solved
+}
+
+/// Try every color for given cell
+/// and return the number of solved cells (Some) or contradiction (None)
+fn probe<B, S>(
+    board: &MutRc<Board<B>>,
+    propagation_solver: &mut propagation::Solver<B>,
+    point: Point,
+) -> PointImpact<B::Color>
+where
+    B: Block,
+    S: LineSolver<BlockType = B>,
+{
+    let vars = {
+        let board = board.read();
+        let cell = board.cell(&point);
+        if cell.is_solved() {
+            info!("Probing expired! {:?}", point);
+        }
+        cell.variants()
+    };
+    debug!("Probing {:?} for variants: {:?}", point, vars);
+
+    vars.into_iter()
+        .map(|assumption| {
+            let impact = probe_color::<B, S>(board, propagation_solver, point, assumption)
+                .map_or_else(
+                    |_err| {
+                        debug!("Contradiction found! {:?}: {:?}", point, assumption);
+                        ProbeResult::Contradiction
+                    },
+                    |new_cells| {
+                        if !new_cells.is_empty() {
+                            debug!(
+                                "Probing {:?}: {:?} brings some new info: {:?}",
+                                point, assumption, new_cells
+                            );
+                        }
+                        ProbeResult::NewInfo(new_cells)
+                    },
+                );
+
+            (assumption, impact)
+        })
+        .collect()
+}
+
 impl<B> FullProbe1<B>
 where
     B: Block,
@@ -298,51 +551,151 @@ where
         self.board().is_solved_full()
     }
 
-    /// Try every color for given cell
-    /// and return the number of solved cells (Some) or contradiction (None)
-    fn probe<S>(&mut self, point: Point) -> PointImpact<B::Color>
+    /// The number of cells probed at once before the results are checked.
+    #[cfg(not(feature = "threaded"))]
+    #[allow(clippy::unused_self)]
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    #[cfg(feature = "threaded")]
+    fn batch_size(&self) -> usize {
+        if self.threads > 1 {
+            self.threads * PROBES_PER_THREAD
+        } else {
+            1
+        }
+    }
+
+    /// Probe the cells one by one on the board itself.
+    #[cfg(not(feature = "threaded"))]
+    fn probe_points<S>(&mut self, points: &[Point]) -> Vec<PointImpact<B::Color>>
     where
         S: LineSolver<BlockType = B>,
     {
-        if self.board().cell(&point).is_solved() {
-            info!("Probing expired! {:?}", point);
+        points
+            .iter()
+            .map(|&point| probe::<B, S>(&self.board, &mut self.propagation_solver, point))
+            .collect()
+    }
+
+    /// Split the cells between the threads, every one probing its own copy of the board.
+    /// All the cells are probed on the same board state, even if some of them are contradictory.
+    ///
+    /// The copies share the line solutions cache with the board (so the cache counters
+    /// include all the threads) and do not fire the board callbacks.
+    #[cfg(feature = "threaded")]
+    fn probe_points<S>(&mut self, points: &[Point]) -> Vec<PointImpact<B::Color>>
+    where
+        S: LineSolver<BlockType = B>,
+    {
+        if points.len() < 2 || self.threads < 2 {
+            return points
+                .iter()
+                .map(|&point| probe::<B, S>(&self.board, &mut self.propagation_solver, point))
+                .collect();
         }
 
-        let vars = self.board().cell(&point).variants();
-        debug!("Probing {:?} for variants: {:?}", point, vars);
+        self.sync_workers();
+        // the points are not empty here
+        let chunk_size = (points.len() - 1) / self.threads + 1;
+
+        crossbeam_utils::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .workers
+                .iter_mut()
+                .zip(points.chunks(chunk_size))
+                .map(|(worker, chunk)| {
+                    scope.spawn(move |_| {
+                        chunk
+                            .iter()
+                            .map(|&point| {
+                                probe::<B, S>(&worker.board, &mut worker.propagation_solver, point)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("The probing thread panicked"))
+                .collect()
+        })
+        .expect("Cannot probe the cells in parallel")
+    }
 
-        vars.into_iter()
-            .map(|assumption| {
-                let save = self.board().make_snapshot();
-                Board::set_color_with_callback(&self.board, &point, &assumption);
+    /// Create the board copies for the threads or bring the existing ones up to date.
+    #[cfg(feature = "threaded")]
+    fn sync_workers(&mut self) {
+        if self.workers.is_empty() {
+            let cache = self.propagation_solver.shared_cache();
+            self.workers = (0..self.threads)
+                .map(|_| {
+                    let board = MutRc::new(self.board().clone());
+                    let propagation_solver = cache.clone().map_or_else(
+                        || propagation::Solver::new(MutRc::clone(&board)),
+                        |cache| propagation::Solver::with_shared_cache(MutRc::clone(&board), cache),
+                    );
+                    ProbeWorker {
+                        board,
+                        propagation_solver,
+                    }
+                })
+                .collect();
+            return;
+        }
 
-                let solved = self.run_propagation::<S>(&point);
+        let cells = self.board().make_snapshot();
+        for worker in &self.workers {
+            worker.board.write().restore(cells.clone());
+        }
+    }
 
-                let impact = solved.ok().map_or_else(
-                    || {
-                        debug!("Contradiction found! {:?}: {:?}", point, assumption);
-                        ProbeResult::Contradiction
-                    },
-                    |new_cells| {
-                        // get the color of every changed cell before restoring
-                        let new_cells: Vec<_> = new_cells
-                            .into_iter()
-                            .map(|point| (point, self.board().cell(&point)))
-                            .collect();
+    /// The probing priority of every unsolved cell in the board order:
+    /// the number of the solved neighbours plus the solution rates of the cell's row and column.
+    ///
+    /// The cells with the highest priority are probed first.
+    pub fn cell_priorities(&self) -> Vec<(Point, f64)> {
+        let board = self.board();
+        let unsolved = board.unsolved_cells();
 
-                        if !new_cells.is_empty() {
-                            debug!(
-                                "Probing {:?}: {:?} brings some new info: {:?}",
-                                point, assumption, new_cells
-                            );
-                        }
-                        ProbeResult::NewInfo(new_cells)
-                    },
-                );
-                Board::restore_with_callback(&self.board, save);
+        let mut row_rate_cache = Vec::with_none(board.height());
+        let mut column_rate_cache = Vec::with_none(board.width());
 
-                (assumption, impact)
+        unsolved
+            .map(|point| {
+                let no_solved = 4 - board.unsolved_neighbours(&point).count();
+                let row_rate = row_rate_cache
+                    .unwrap_or_insert_with(point.y, || board.row_solution_rate(point.y));
+                let column_rate = column_rate_cache
+                    .unwrap_or_insert_with(point.x, || board.column_solution_rate(point.x));
+
+                #[allow(clippy::cast_precision_loss)]
+                let priority = no_solved as f64 + row_rate + column_rate;
+                (point, priority)
             })
             .collect()
     }
+
+    /// What would follow if the cell has the given color?
+    ///
+    /// Return the cells (with their colors) solved by that assumption
+    /// or the contradiction if the color is impossible.
+    /// The board does not change.
+    #[allow(dead_code)]
+    pub fn probe_cell<S>(
+        &mut self,
+        point: Point,
+        color: B::Color,
+    ) -> Result<Vec<(Point, B::Color)>, UnsolvableLine>
+    where
+        S: LineSolver<BlockType = B>,
+    {
+        if !self.board().cell(&point).variants().contains(&color) {
+            return Err(UnsolvableLine);
+        }
+
+        probe_color::<B, S>(&self.board, &mut self.propagation_solver, point, color)
+    }
 }
@@ -1,10 +1,15 @@
-use std::{fmt::Debug, hash::Hash};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
 
 use hashbrown::HashSet;
 use log::{debug, warn};
 
 use crate::{
-    block::{Block, Line},
+    block::{Block, Color, Description, Line},
     board::{Board, LineDirection, LinePosition, Point},
     cache::{cache_info, Cached, GrowableCache},
     solver::line::{self, LineSolver, UnsolvableLine},
@@ -20,8 +25,9 @@ where
     B: Block,
 {
     board: MutRc<Board<B>>,
-    cache_rows: Option<LineSolverCache<B>>,
-    cache_cols: Option<LineSolverCache<B>>,
+    cache: Option<SharedCache<B>>,
+    deadline: Option<Instant>,
+    timed_out: bool,
 }
 
 trait JobQueue<T> {
@@ -121,12 +127,14 @@ where
     }
 }
 
+/// The key does not depend on the line position,
+/// so the solutions can be reused for the same lines of any board.
 #[derive(Debug, PartialEq, Eq, Hash)]
 struct CacheKey<B>
 where
     B: Block,
 {
-    line_index: usize,
+    description: ReadRc<Description<B>>,
     source: Line<B::Color>,
 }
 
@@ -135,11 +143,56 @@ type LineSolverCache<B> = GrowableCache<CacheKey<B>, CacheValue<B>>;
 
 const MAX_CACHE_ENTRIES_PER_LINE: usize = 2000;
 
-fn new_cache<B>(capacity: usize) -> LineSolverCache<B>
+static CACHE_ENTRIES_PER_LINE: AtomicUsize = AtomicUsize::new(MAX_CACHE_ENTRIES_PER_LINE);
+
+/// Change the number of the cached line solutions per every row and column
+/// for the solvers created with the `Solver::with_cache` (e.g. by the probing) after this call.
+/// Lower it to save the memory on the huge puzzles.
+///
+/// To bound the cache of a single solver, use the `Solver::with_cache_capacity`.
+pub fn set_default_cache_entries_per_line(entries: usize) {
+    CACHE_ENTRIES_PER_LINE.store(entries, Ordering::Relaxed);
+}
+
+/// The line solutions cache which is not tied to a single solver or board.
+///
+/// Clone it to share the solutions between the solvers of many puzzles
+/// with the same line clues (e.g. in a batch of similar puzzles).
+#[derive(Debug)]
+pub struct SharedCache<B>(MutRc<LineSolverCache<B>>)
+where
+    B: Block;
+
+impl<B> SharedCache<B>
+where
+    B: Block,
+{
+    /// Memoize at most `capacity` line solutions.
+    /// The least recently used solutions are evicted when the capacity is exceeded.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(MutRc::new(GrowableCache::with_capacity(capacity)))
+    }
+
+    /// The number of memoized line solutions.
+    #[allow(dead_code)]
+    pub fn size(&self) -> usize {
+        self.0.read().cache_size()
+    }
+
+    /// Drop all the memoized line solutions (for all the solvers sharing the cache).
+    #[allow(dead_code)]
+    pub fn clear(&self) {
+        self.0.write().cache_clear();
+    }
+}
+
+impl<B> Clone for SharedCache<B>
 where
     B: Block,
 {
-    GrowableCache::with_capacity(capacity)
+    fn clone(&self) -> Self {
+        Self(MutRc::clone(&self.0))
+    }
 }
 
 impl<B> Solver<B>
@@ -150,15 +203,49 @@ where
     pub fn new(board: MutRc<Board<B>>) -> Self {
         Self {
             board,
-            cache_rows: None,
-            cache_cols: None,
+            cache: None,
+            deadline: None,
+            timed_out: false,
         }
     }
 
+    /// Stop solving the lines when the deadline is reached,
+    /// keeping all the cells solved so far.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Whether the last `run` was interrupted by the deadline.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    fn deadline_reached(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
     pub fn with_cache(board: MutRc<Board<B>>) -> Self {
+        let capacity = {
+            let board = board.read();
+            CACHE_ENTRIES_PER_LINE.load(Ordering::Relaxed) * (board.width() + board.height())
+        };
+        Self::with_cache_capacity(board, capacity)
+    }
+
+    /// Memoize at most `capacity` line solutions for rows and columns together.
+    /// The least recently used solutions are evicted when the capacity is exceeded.
+    pub fn with_cache_capacity(board: MutRc<Board<B>>, capacity: usize) -> Self {
+        Self::with_shared_cache(board, SharedCache::with_capacity(capacity))
+    }
+
+    /// Use the cache owned by the caller, so the line solutions
+    /// outlive the solver and can be reused for the other boards.
+    pub fn with_shared_cache(board: MutRc<Board<B>>, cache: SharedCache<B>) -> Self {
         let mut self_ = Self::new(board);
 
-        self_.init_cache();
+        self_.cache = Some(cache);
         self_
     }
 
@@ -166,51 +253,54 @@ where
         self.board.read()
     }
 
-    fn init_cache(&mut self) {
-        let width = self.board().width();
-        let height = self.board().height();
+    fn cached_solution(&mut self, key: &CacheKey<B>) -> Option<CacheValue<B>> {
+        self.cache
+            .as_ref()
+            .and_then(|cache| cache.0.write().cache_get(key).cloned())
+    }
 
-        self.cache_rows = Some(new_cache(MAX_CACHE_ENTRIES_PER_LINE * height));
-        self.cache_cols = Some(new_cache(MAX_CACHE_ENTRIES_PER_LINE * width));
+    fn set_cached_solution(&mut self, key: CacheKey<B>, solved: CacheValue<B>) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.0.write().cache_set(key, solved);
+        }
     }
 
-    fn cached_solution(
-        &mut self,
-        direction: LineDirection,
-        key: &CacheKey<B>,
-    ) -> Option<CacheValue<B>> {
-        let cache = match direction {
-            LineDirection::Row => self.cache_rows.as_mut(),
-            LineDirection::Column => self.cache_cols.as_mut(),
-        };
+    /// Drop all the memoized line solutions to release the memory.
+    #[allow(dead_code)]
+    pub fn clear_cache(&mut self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.clear();
+        }
+    }
 
-        cache.and_then(|cache| cache.cache_get(key).cloned())
+    /// The line solutions cache (if any) to share with the other solvers.
+    #[cfg(feature = "threaded")]
+    pub(crate) fn shared_cache(&self) -> Option<SharedCache<B>> {
+        self.cache.clone()
     }
 
-    fn set_cached_solution(
-        &mut self,
-        direction: LineDirection,
-        key: CacheKey<B>,
-        solved: CacheValue<B>,
-    ) {
-        let cache = match direction {
-            LineDirection::Row => self.cache_rows.as_mut(),
-            LineDirection::Column => self.cache_cols.as_mut(),
-        };
+    /// The number of memoized line solutions for both rows and columns.
+    #[allow(dead_code)]
+    pub fn cache_size(&self) -> usize {
+        self.cache.as_ref().map_or(0, SharedCache::size)
+    }
 
-        if let Some(cache) = cache {
-            cache.cache_set(key, solved);
-        }
+    /// The total number of the cache lookups that found and did not find the line solution.
+    #[allow(dead_code)]
+    pub fn cache_hits_and_misses(&self) -> (usize, usize) {
+        self.cache.as_ref().map_or((0, 0), |cache| {
+            let cache = cache.0.read();
+            (
+                cache.cache_hits().unwrap_or(0) as usize,
+                cache.cache_misses().unwrap_or(0) as usize,
+            )
+        })
     }
 
     fn print_cache_info(&self) {
-        if let Some(cache) = &self.cache_cols {
-            let (s, h, r) = cache_info(cache);
-            warn!("Cache columns: Size={}, hits={}, hit rate={}.", s, h, r);
-        }
-        if let Some(cache) = &self.cache_rows {
-            let (s, h, r) = cache_info(cache);
-            warn!("Cache rows: Size={}, hits={}, hit rate={}.", s, h, r);
+        if let Some(cache) = &self.cache {
+            let (s, h, r) = cache_info(&*cache.0.read());
+            warn!("Cache: Size={}, hits={}, hit rate={}.", s, h, r);
         }
     }
 
@@ -218,17 +308,31 @@ where
     where
         S: LineSolver<BlockType = B>,
     {
-        if let Some(point) = point {
+        let solved_cells = if let Some(point) = point {
             debug!("Solving {:?}", point);
             let queue = SmallJobQueue::with_point(point);
-            self.run_jobs::<S, _>(queue)
+            self.run_jobs::<S, _>(queue)?
         } else {
             let queue = {
                 let board = self.board();
                 LongJobQueue::with_height_and_width(board.height(), board.width())
             };
-            self.run_jobs::<S, _>(queue)
+            self.run_jobs::<S, _>(queue)?
+        };
+
+        if !solved_cells.is_empty() {
+            self.board().notify_batch_change(&solved_cells);
         }
+        Ok(solved_cells)
+    }
+
+    /// Same as the `run` from the given point, but the batch change callback is not called,
+    /// since the probed board is restored right after that.
+    pub(crate) fn run_probe<S>(&mut self, point: Point) -> Result<Vec<Point>, UnsolvableLine>
+    where
+        S: LineSolver<BlockType = B>,
+    {
+        self.run_jobs::<S, _>(SmallJobQueue::with_point(point))
     }
 
     fn run_jobs<S, Q>(&mut self, mut queue: Q) -> Result<Vec<Point>, UnsolvableLine>
@@ -237,9 +341,25 @@ where
         Q: JobQueue<LinePosition>,
     {
         let mut lines_solved = 0_u32;
+        let mut lines_skipped = 0_u32;
         let mut solved_cells = vec![];
+        self.timed_out = false;
 
         while let Some(line_pos) = queue.pop() {
+            if self.deadline_reached() {
+                warn!(
+                    "Propagation interrupted by the timeout ({} lines solved)",
+                    lines_solved
+                );
+                self.timed_out = true;
+                break;
+            }
+
+            if self.is_line_solved(line_pos)? {
+                lines_skipped += 1;
+                continue;
+            }
+
             if let Some(updated_indexes) = self.update_line::<S>(line_pos)? {
                 let solved_points = updated_indexes
                     .iter()
@@ -256,10 +376,31 @@ where
             lines_solved += 1;
         }
 
-        debug!("Lines solved: {}", lines_solved);
+        debug!(
+            "Lines solved: {}, already solved lines skipped: {}",
+            lines_solved, lines_skipped
+        );
         Ok(solved_cells)
     }
 
+    /// Whether all the cells in the line are already solved,
+    /// so there is no need to call the line solver.
+    /// Checking the line against the description is much cheaper than solving it.
+    fn is_line_solved(&self, position: LinePosition) -> Result<bool, UnsolvableLine> {
+        let board = self.board();
+        let line = board.get_line(position);
+        if !line.iter().all(|cell| cell.is_solved()) {
+            return Ok(false);
+        }
+
+        if board.description(position).matches_line(&line) {
+            Ok(true)
+        } else {
+            debug!("The solved {:?} does not match its description", position);
+            Err(UnsolvableLine)
+        }
+    }
+
     /// Solve a line with the solver S and update the board.
     /// If the line gets partially solved, put the crossed lines into queue.
     ///
@@ -274,16 +415,15 @@ where
         let (cache_key, line) = {
             let board = self.board();
             let line = board.get_line(position);
-            let cache_index = board.cache_index(position);
 
             let key = CacheKey {
-                line_index: cache_index,
+                description: board.description(position),
                 source: ReadRc::clone(&line),
             };
             (key, line)
         };
 
-        let cached = self.cached_solution(position.direction(), &cache_key);
+        let cached = self.cached_solution(&cache_key);
 
         let solution = cached.unwrap_or_else(|| {
             let line_desc = self.board().description(position);
@@ -294,7 +434,7 @@ where
             );
             let value = line::solve::<S, _>(line_desc, ReadRc::clone(&line));
 
-            self.set_cached_solution(position.direction(), cache_key, value.clone());
+            self.set_cached_solution(cache_key, value.clone());
             value
         })?;
 
@@ -357,3 +497,145 @@ where
         self.print_cache_info();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Instant,
+    };
+
+    use crate::{
+        block::{
+            base::clues_from_solution,
+            binary::{BinaryBlock, BinaryColor},
+            Description, Line,
+        },
+        board::Board,
+        solver::line::{DynamicSolver, LineSolver, UnsolvableLine},
+        utils::rc::{MutRc, ReadRc},
+    };
+
+    use super::{
+        set_default_cache_entries_per_line, SharedCache, Solver, MAX_CACHE_ENTRIES_PER_LINE,
+    };
+
+    static SOLVER_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingSolver(DynamicSolver<BinaryBlock>);
+
+    impl LineSolver for CountingSolver {
+        type BlockType = BinaryBlock;
+
+        fn new(desc: ReadRc<Description<BinaryBlock>>, line: Line<BinaryColor>) -> Self {
+            let _ = SOLVER_CALLS.fetch_add(1, Ordering::SeqCst);
+            Self(DynamicSolver::new(desc, line))
+        }
+
+        fn solve(&mut self) -> Result<(), UnsolvableLine> {
+            self.0.solve()
+        }
+
+        fn into_solution(self) -> Line<BinaryColor> {
+            self.0.into_solution()
+        }
+    }
+
+    /// A staircase is solvable by the lines only
+    fn staircase(size: usize) -> MutRc<Board<BinaryBlock>> {
+        let solution: Vec<Vec<_>> = (0..size)
+            .map(|i| (0..size).map(|j| if j <= i { 1 } else { 0 }).collect())
+            .collect();
+        let (columns, rows) = clues_from_solution(&solution, 0);
+        MutRc::new(Board::with_descriptions_and_palette(rows, columns, None))
+    }
+
+    #[test]
+    fn solved_lines_skipped() {
+        let size = 30;
+        let board = staircase(size);
+
+        let mut solver = Solver::new(MutRc::clone(&board));
+        let _ = solver.run::<CountingSolver>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        let first_sweep = SOLVER_CALLS.swap(0, Ordering::SeqCst);
+        // every line is solved at most once and the rest are skipped
+        assert!(first_sweep <= 2 * size, "{} calls", first_sweep);
+
+        let _ = solver.run::<CountingSolver>(None).unwrap();
+        assert_eq!(SOLVER_CALLS.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn deadline() {
+        let board = staircase(10);
+        let mut solver = Solver::new(MutRc::clone(&board));
+        solver.set_deadline(Instant::now());
+
+        let solved = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(solver.timed_out());
+        assert!(solved.is_empty());
+        assert!(!board.read().is_solved_full());
+    }
+
+    #[test]
+    fn clear_cache() {
+        let board = staircase(10);
+        let mut solver = Solver::with_cache(MutRc::clone(&board));
+        assert_eq!(solver.cache_size(), 0);
+
+        let _ = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        assert!(solver.cache_size() > 0);
+
+        solver.clear_cache();
+        assert_eq!(solver.cache_size(), 0);
+    }
+
+    #[test]
+    fn cache_capacity() {
+        let board = staircase(10);
+        let mut solver = Solver::with_cache_capacity(MutRc::clone(&board), 5);
+
+        let _ = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        assert_eq!(solver.cache_size(), 5);
+    }
+
+    #[test]
+    fn default_cache_entries_per_line() {
+        set_default_cache_entries_per_line(1);
+        let board = staircase(10);
+        let mut solver = Solver::with_cache(MutRc::clone(&board));
+        set_default_cache_entries_per_line(MAX_CACHE_ENTRIES_PER_LINE);
+
+        let _ = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        // one entry for every of the 10 rows and 10 columns
+        assert_eq!(solver.cache_size(), 20);
+    }
+
+    #[test]
+    fn shared_cache() {
+        let cache = SharedCache::with_capacity(1000);
+
+        let board = staircase(10);
+        let mut solver = Solver::with_shared_cache(MutRc::clone(&board), cache.clone());
+        let _ = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        let size = cache.size();
+        assert!(size > 0);
+        assert_eq!(solver.cache_size(), size);
+        drop(solver);
+
+        // the same lines on the other board are already in the cache
+        let board = staircase(10);
+        let mut solver = Solver::with_shared_cache(MutRc::clone(&board), cache.clone());
+        let _ = solver.run::<DynamicSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+        assert_eq!(cache.size(), size);
+
+        solver.clear_cache();
+        assert_eq!(cache.size(), 0);
+    }
+}
@@ -183,7 +183,9 @@ where
         clues
             .iter()
             .map(|clue| {
-                let positions = clue.positions_number(line_length);
+                let positions = clue
+                    .positions_number(line_length)
+                    .expect("The blocks should fit the line");
                 LinePositions(
                     clue.block_starts()
                         .iter()
@@ -515,6 +517,11 @@ where
         })
     }
 
+    /// Enumerate the solutions lazily, stopping after the `solutions_number` ones
+    /// (the solver does not run again once the limit is reached).
+    ///
+    /// The `None` means all the solutions, which can be astronomically many
+    /// for the under-constrained puzzles.
     pub fn run(
         &self,
         probing_impact: Impact<B>,
@@ -546,7 +553,11 @@ where
         solver.add_formula(&formula);
 
         let mut found = 0;
+        let mut exhausted = false;
         from_fn(move || {
+            if exhausted {
+                return None;
+            }
             if let Some(solutions_number) = solutions_number {
                 if found >= solutions_number {
                     return None;
@@ -554,7 +565,9 @@ where
             }
 
             let _ = solver.solve().unwrap();
-            solver.model().map(|model| {
+            let model = solver.model();
+            exhausted = model.is_none();
+            model.map(|model| {
                 found += 1;
 
                 let cells = &model[block_vars..];
@@ -1,3 +1,5 @@
+//! The general helpers for the puzzle-like text formats.
+
 use std::{
     cmp::PartialOrd,
     fmt::Display,
@@ -8,6 +10,8 @@ use std::{
 
 use hashbrown::{HashMap, HashSet};
 
+/// Align the value to the left (if `right` is set, so the spaces go to the right)
+/// or to the right in the field of given width.
 pub fn pad<T>(s: &T, max_size: usize, right: bool) -> String
 where
     T: Display,
@@ -19,12 +23,13 @@ where
     }
 }
 
-pub fn pad_with<T: Clone>(v: &mut Vec<T>, el: T, max_size: usize, right: bool) {
+pub(crate) fn pad_with<T: Clone>(v: &mut Vec<T>, el: T, max_size: usize, right: bool) {
     if let Some(additional) = max_size.checked_sub(v.len()) {
         if additional == 0 {
             return;
         }
 
+        #[allow(clippy::manual_repeat_n)]
         let plus = std::iter::repeat(el).take(additional);
 
         if right {
@@ -36,6 +41,9 @@ pub fn pad_with<T: Clone>(v: &mut Vec<T>, el: T, max_size: usize, right: bool) {
     }
 }
 
+/// Swap the rows and the columns of the matrix.
+///
+/// Fails if the rows have different lengths.
 pub fn transpose<T: Clone>(input: &[Vec<T>]) -> Result<Vec<Vec<T>>, String> {
     if input.is_empty() {
         return Ok(vec![]);
@@ -54,7 +62,7 @@ pub fn transpose<T: Clone>(input: &[Vec<T>]) -> Result<Vec<Vec<T>>, String> {
         .collect())
 }
 
-pub fn replace<T>(vec: &mut [T], what: &T, with_what: &T)
+pub(crate) fn replace<T>(vec: &mut [T], what: &T, with_what: &T)
 where
     T: PartialEq + Clone,
 {
@@ -69,7 +77,7 @@ where
     }
 }
 
-pub fn two_powers(num: u32) -> impl Iterator<Item = u32> {
+pub(crate) fn two_powers(num: u32) -> impl Iterator<Item = u32> {
     (0..num.count_ones()).scan(num, |num, _i| {
         let prev = *num;
         *num = prev & (prev - 1);
@@ -77,11 +85,11 @@ pub fn two_powers(num: u32) -> impl Iterator<Item = u32> {
     })
 }
 
-pub fn from_two_powers(numbers: &[u32]) -> u32 {
+pub(crate) fn from_two_powers(numbers: &[u32]) -> u32 {
     numbers.iter().fold(0, |acc, &x| acc | x)
 }
 
-pub fn dedup<T>(vec: impl Iterator<Item = T>) -> Vec<T>
+pub(crate) fn dedup<T>(vec: impl Iterator<Item = T>) -> Vec<T>
 where
     T: Eq + Hash + Clone,
 {
@@ -89,7 +97,7 @@ where
     set.into_iter().collect()
 }
 
-pub fn abs_sub<T>(a: T, b: T) -> T::Output
+pub(crate) fn abs_sub<T>(a: T, b: T) -> T::Output
 where
     T: PartialOrd + Sub,
 {
@@ -100,7 +108,7 @@ where
     }
 }
 
-pub mod iter {
+pub(crate) mod iter {
     pub trait FindOk: Iterator {
         /// Generalization of `find_map` for `Result` type.
         /// If the iterator is exhausted, return `on_empty_error`.
@@ -167,7 +175,7 @@ pub mod iter {
     }
 }
 
-pub fn product<T, U>(s1: &[T], s2: &[U]) -> Vec<(T, U)>
+pub(crate) fn product<T, U>(s1: &[T], s2: &[U]) -> Vec<(T, U)>
 where
     T: Clone,
     U: Clone,
@@ -180,7 +188,7 @@ where
 #[cfg(feature = "sat")]
 /// Produce Vec of unique ordered pairs (x, y)
 /// where idx(x) < idx(y)
-pub fn pair_combinations<T>(s: &[T]) -> Vec<(T, T)>
+pub(crate) fn pair_combinations<T>(s: &[T]) -> Vec<(T, T)>
 where
     T: Clone,
 {
@@ -218,6 +226,11 @@ where
     )
 }
 
+/// Split the text into the sections started with the lines
+/// exactly equal (after trimming) to the given names.
+/// The lines before the first found section go to the `first_section` (or to the empty name).
+///
+/// Fails if any section is not found or repeated.
 pub fn split_sections<'a, 'b>(
     text: &'a str,
     section_names: &'b [&'b str],
@@ -262,7 +275,10 @@ pub fn split_sections<'a, 'b>(
     Ok(res)
 }
 
-pub trait Stripper<P> {
+/// The backport of `str::strip_prefix` and `str::strip_suffix` for Rust<1.45.
+/// On the newer versions the inherent methods always win, so the trait is never used.
+#[allow(dead_code)]
+pub(crate) trait Stripper<P> {
     fn strip_prefix(&self, pattern: P) -> Option<&Self>;
     fn strip_suffix(&self, pattern: P) -> Option<&Self>;
 }
@@ -297,8 +313,11 @@ impl_stripper!(char, len_utf8);
 impl_stripper!(&Self, len);
 
 #[cfg(not(feature = "sat"))]
-pub mod time {
-    use std::time::Instant;
+pub(crate) mod time {
+    use std::{
+        convert::TryFrom,
+        time::{Duration, Instant},
+    };
 
     #[cfg(feature = "std_time")]
     #[allow(clippy::unnecessary_wraps)]
@@ -310,9 +329,21 @@ pub mod time {
     pub const fn now() -> Option<Instant> {
         None
     }
+
+    /// The moment the given number of seconds is elapsed (if the time is available).
+    pub fn deadline(seconds: u32) -> Option<Instant> {
+        now().map(|now| now + Duration::from_secs(seconds.into()))
+    }
+
+    /// The whole seconds left until the deadline (rounded up).
+    pub fn seconds_left(deadline: Instant) -> u32 {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+        u32::try_from(seconds).unwrap_or(u32::MAX)
+    }
 }
 
-pub mod rc {
+pub(crate) mod rc {
     pub use inner::{mutate_ref, read_ref, InteriorMutableRef, MutRef, ReadRc, ReadRef};
 
     #[cfg(feature = "threaded")]
@@ -549,6 +580,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::single_range_in_vec_init)]
     fn to_ranges_unsorted() {
         let vec = vec![9, 5];
         assert_eq!(idx_to_ranges(vec), Some(vec![5..9]));
@@ -1,8 +1,10 @@
 #[cfg(feature = "ini")]
 mod ini {
+    use std::{convert::TryFrom, fs};
+
     use nonogrid::{
         parser::{LocalReader, MyFormat, Paletted, PuzzleScheme},
-        BoardParser,
+        BinaryBlock, Board, BoardParser, ColoredBlock,
     };
 
     #[test]
@@ -28,6 +30,76 @@ mod ini {
         assert_eq!(palette.id_by_name("b"), Some(4));
         assert_eq!(palette.id_by_name("r"), Some(8));
     }
+
+    #[test]
+    fn color_count() {
+        let f = MyFormat::read_local("examples/UK.toml").unwrap();
+        assert_eq!(f.color_count().unwrap(), 2);
+
+        let f = MyFormat::read_local("examples/MLP.toml").unwrap();
+        assert_eq!(f.color_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn try_parse_checks_scheme() {
+        let f = MyFormat::read_local("examples/UK.toml").unwrap();
+        assert!(f.try_parse::<BinaryBlock>().is_err());
+        assert!(f.try_parse::<ColoredBlock>().is_ok());
+
+        let f = MyFormat::read_local("examples/MLP.toml").unwrap();
+        assert!(f.try_parse::<BinaryBlock>().is_ok());
+    }
+
+    #[test]
+    fn try_from_str_matching_scheme() {
+        let content = fs::read_to_string("examples/UK.toml").unwrap();
+        let board = Board::<ColoredBlock>::try_from_str(&content).unwrap();
+        assert_eq!(board.dimensions().area(), 31 * 15);
+
+        let content = fs::read_to_string("examples/MLP.toml").unwrap();
+        assert!(Board::<BinaryBlock>::try_from(content.as_str()).is_ok());
+    }
+
+    #[test]
+    fn try_from_str_mismatched_scheme() {
+        let content = fs::read_to_string("examples/UK.toml").unwrap();
+        let err = Board::<BinaryBlock>::try_from_str(&content).unwrap_err();
+        assert_eq!(
+            err.0,
+            "The puzzle has the MultiColor scheme, but the BlackAndWhite is requested"
+        );
+
+        let content = fs::read_to_string("examples/MLP.toml").unwrap();
+        assert!(Board::<ColoredBlock>::try_from(content.as_str()).is_err());
+    }
+
+    #[test]
+    fn try_from_str_too_big() {
+        let clues = vec!["1"; 5000].join(", ");
+        let content = format!("[clues]\nrows = \"{}\"\ncolumns = \"{}\"\n", clues, clues);
+
+        let err = Board::<BinaryBlock>::try_from_str(&content).unwrap_err();
+        assert_eq!(
+            err.0,
+            "The board 5000x5000 exceeds the maximum area of 16777216 cells"
+        );
+
+        let f = MyFormat::with_content(&content).unwrap();
+        assert!(f.try_parse::<BinaryBlock>().is_err());
+    }
+
+    #[test]
+    fn color_count_too_big() {
+        let clues = vec!["1r"; 5000].join(", ");
+        let content = format!(
+            "[clues]\nrows = \"{}\"\ncolumns = \"{}\"\n[colors]\ndefs = [\"r=(255, 0, 0) %\"]\n",
+            clues, clues
+        );
+
+        let f = MyFormat::with_content(&content).unwrap();
+        assert_eq!(f.infer_scheme(), PuzzleScheme::MultiColor);
+        assert!(f.color_count().is_err());
+    }
 }
 
 #[cfg(feature = "web")]
@@ -71,3 +143,45 @@ mod webpbn {
         assert_eq!(palette.id_by_name("white"), Some(1));
     }
 }
+
+mod external_parser {
+    use nonogrid::{
+        parser::{ParseError, PuzzleScheme},
+        BinaryBlock, Block, Board, BoardParser, Description,
+    };
+
+    /// The empty square board, implementing only the required methods of the parser
+    #[derive(Debug)]
+    struct EmptySquare(usize);
+
+    impl BoardParser for EmptySquare {
+        fn with_content(content: &str) -> Result<Self, ParseError> {
+            content
+                .trim()
+                .parse()
+                .map(Self)
+                .map_err(|err| ParseError(err.to_string()))
+        }
+
+        fn parse<B>(&self) -> Board<B>
+        where
+            B: Block,
+        {
+            let lines = vec![Description::new(vec![]); self.0];
+            Board::with_descriptions_and_palette(lines.clone(), lines, None)
+        }
+
+        fn infer_scheme(&self) -> PuzzleScheme {
+            PuzzleScheme::BlackAndWhite
+        }
+    }
+
+    #[test]
+    fn provided_build() {
+        let parser = EmptySquare::with_content("3").unwrap();
+        let board = parser.try_parse::<BinaryBlock>().unwrap();
+        assert_eq!(board.width(), 3);
+        assert_eq!(board.height(), 3);
+        assert_eq!(parser.color_count().unwrap(), 1);
+    }
+}
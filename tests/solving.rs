@@ -1,3 +1,16 @@
+use nonogrid::{clues_from_solution, BinaryBlock, Board, RcBoard};
+
+/// The black-and-white board with the clues of the given solution
+fn make_board(solution: &[Vec<u32>]) -> Board<BinaryBlock> {
+    let (columns, rows) = clues_from_solution(solution, 0);
+    Board::with_descriptions_and_palette(rows, columns, None)
+}
+
+/// The 2x2 diagonal has two solutions
+fn ambiguous_board() -> RcBoard<BinaryBlock> {
+    RcBoard::new(make_board(&[vec![1, 0], vec![0, 1]]))
+}
+
 #[cfg(feature = "ini")]
 mod ini {
     use std::f64;
@@ -6,16 +19,14 @@ mod ini {
 
     use nonogrid::{
         parser::{LocalReader, MyFormat, PuzzleScheme},
+        render::{NullRenderer, Renderer, ShellRenderer},
         BinaryBlock, BoardParser, ColoredBlock, FullProbe, LineSolver, ProbeSolver,
         PropagationSolver,
     };
 
     #[test]
     fn hello() {
-        use nonogrid::{
-            render::{Renderer, ShellRenderer},
-            BinaryColor,
-        };
+        use nonogrid::BinaryColor;
 
         let f = MyFormat::read_local("examples/hello.toml").unwrap();
         let board = f.parse_rc::<BinaryBlock>();
@@ -38,6 +49,12 @@ mod ini {
             println!("{}", color_callback_renderer.render())
         });
 
+        let batch_callback_renderer = ShellRenderer::with_board(board.clone());
+        board.write().set_callback_on_batch_change(move |points| {
+            println!("Changed {} cells", points.len());
+            println!("{}", batch_callback_renderer.render())
+        });
+
         warn!("Solving with simple line propagation");
         let mut solver = PropagationSolver::new(board.clone());
         solver.run::<LineSolver<_>>(None).unwrap();
@@ -56,6 +73,95 @@ mod ini {
         );
     }
 
+    #[test]
+    fn null_renderer() {
+        let f = MyFormat::read_local("examples/hello.toml").unwrap();
+        let board = f.parse_rc::<BinaryBlock>();
+
+        let renderer = NullRenderer::with_board(board.clone());
+        let mut solver = PropagationSolver::new(board.clone());
+        solver.run::<LineSolver<_>>(None).unwrap();
+
+        assert!(board.read().is_solved_full());
+        assert_eq!(renderer.render(), "");
+        assert_eq!(renderer.render_simple(), "");
+    }
+
+    #[test]
+    fn custom_symbols() {
+        use nonogrid::BinaryColor;
+
+        let f = MyFormat::read_local("examples/hello.toml").unwrap();
+        let board = f.parse_rc::<BinaryBlock>();
+
+        let renderer = ShellRenderer::with_board(board.clone()).with_unknown_symbol('_');
+        assert!(renderer
+            .render_simple()
+            .chars()
+            .all(|c| c == '_' || c == '\n'));
+
+        let mut solver = PropagationSolver::new(board.clone());
+        solver.run::<LineSolver<_>>(None).unwrap();
+
+        let renderer = ShellRenderer::with_board(board.clone())
+            .with_white_symbol(' ')
+            .with_black_symbol('#');
+        let expected: Vec<String> = board
+            .read()
+            .iter_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&cell| if cell == BinaryColor::Black { '#' } else { ' ' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(renderer.render_simple(), expected.join("\n"));
+
+        let renderer = ShellRenderer::with_board(board.clone())
+            .with_white_symbol(' ')
+            .with_black_symbol('#');
+        let grid = renderer.render_grid();
+        assert_eq!(
+            grid,
+            expected
+                .join("\n")
+                .replace(' ', ".")
+                .replace('#', "\u{25A0}")
+        );
+
+        // the defaults are not changed
+        let renderer = ShellRenderer::with_board(board);
+        assert!(renderer.render_simple().contains('.'));
+        assert!(!renderer.render_simple().contains('#'));
+    }
+
+    #[test]
+    fn custom_black_symbol_colored() {
+        // the default color is named 'B' here, not 'black'
+        let f = MyFormat::with_content(
+            r#"
+[colors]
+defs = ['r = (red) %']
+
+[clues]
+rows = "2 1r"
+columns = "1, 1, 1r"
+"#,
+        )
+        .unwrap();
+        let board = f.parse_rc::<ColoredBlock>();
+
+        let mut solver = PropagationSolver::new(board.clone());
+        solver.run::<LineSolver<_>>(None).unwrap();
+        assert!(board.read().is_solved_full());
+
+        let renderer = ShellRenderer::with_board(board).with_black_symbol('#');
+        let rendered = renderer.render_simple();
+        assert!(rendered.starts_with("##"));
+        // the red cell keeps its palette rendering
+        assert_eq!(rendered.matches('#').count(), 2);
+    }
+
     #[test]
     fn pony() {
         let f = MyFormat::read_local("examples/MLP.toml").unwrap();
@@ -81,6 +187,69 @@ mod ini {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "sat"))]
+    fn difficulty() {
+        use nonogrid::{classify_difficulty, DifficultyTier};
+
+        let f = MyFormat::read_local("examples/hello.toml").unwrap();
+        let board = f.parse::<BinaryBlock>();
+        let tier = classify_difficulty::<_, LineSolver<_>, FullProbe<_>>(&board).unwrap();
+        assert_eq!(tier, DifficultyTier::Trivial);
+        assert!(!board.is_solved_full());
+
+        let f = MyFormat::read_local("examples/MLP.toml").unwrap();
+        let board = f.parse::<BinaryBlock>();
+        let tier = classify_difficulty::<_, LineSolver<_>, FullProbe<_>>(&board).unwrap();
+        assert_eq!(tier, DifficultyTier::Easy);
+    }
+
+    #[test]
+    fn pony_limited_probes() {
+        let f = MyFormat::read_local("examples/MLP.toml").unwrap();
+        let board = f.parse_rc::<BinaryBlock>();
+
+        let solver = FullProbe::with_board(board.clone()).with_max_probes(5);
+        assert_eq!(solver.unsolved_cells().len(), 5);
+
+        let mut solver = FullProbe::with_board(board.clone()).with_cache_capacity(100);
+        solver.run_unsolved::<LineSolver<_>>().unwrap();
+        assert!(board.read().is_solved_full());
+        assert!(solver.cache_size() <= 100);
+
+        let board = f.parse_rc::<BinaryBlock>();
+        let solver = FullProbe::with_board(board.clone());
+        let all_cells = board.read().dimensions().area();
+        assert_eq!(solver.unsolved_cells().len(), all_cells);
+    }
+
+    #[test]
+    #[cfg(feature = "threaded")]
+    fn pony_threaded_probes() {
+        let f = MyFormat::read_local("examples/MLP.toml").unwrap();
+
+        let probe = |threads| {
+            let board = f.parse_rc::<BinaryBlock>();
+            let mut solver = FullProbe::with_board(board.clone()).with_threads(threads);
+            solver.run_unsolved::<LineSolver<_>>().unwrap();
+            let cells = board.read().make_snapshot();
+            (cells, solver)
+        };
+
+        let (sequential, _solver) = probe(1);
+        let (parallel, _solver) = probe(4);
+        assert_eq!(parallel, sequential);
+
+        #[cfg(not(feature = "sat"))]
+        {
+            // the threads share the line solutions cache with the board
+            let stats = _solver.stats();
+            assert!(stats.probes > 0);
+            assert!(stats.cache_hits > 0);
+            assert!(stats.cache_misses > 0);
+        }
+    }
+
     #[test]
     fn uk_flag() {
         let p = MyFormat::read_local("examples/UK.toml").unwrap();
@@ -92,9 +261,58 @@ mod ini {
         let mut solver = PropagationSolver::new(board.clone());
         solver.run::<LineSolver<_>>(None).unwrap();
 
-        let board = board.read();
-        assert!(board.is_solved_full());
-        assert!((board.solution_rate() - 1.0).abs() < f64::EPSILON);
+        {
+            let board = board.read();
+            assert!(board.is_solved_full());
+            assert!((board.solution_rate() - 1.0).abs() < f64::EPSILON);
+        }
+
+        let renderer = ShellRenderer::with_board(board.clone());
+        assert!(!renderer.render().contains("red"));
+
+        let grid = ShellRenderer::with_board(board.clone()).render_grid();
+        assert_eq!(board.read().to_string(), grid);
+        let grid: Vec<_> = grid.lines().collect();
+        assert_eq!(grid.len(), board.read().height());
+        assert!(grid
+            .iter()
+            .all(|row| row.chars().count() == board.read().width()));
+        assert!(grid
+            .iter()
+            .all(|row| row.chars().all(|c| ".*%".contains(c))));
+
+        let renderer = ShellRenderer::with_board(board).with_legend(true);
+        let rendered = renderer.render();
+        let legend = rendered.lines().last().unwrap();
+        assert!(legend.contains("*=blue (#0000ff)"));
+        assert!(legend.contains("%=red (#ff0000)"));
+
+        let mut streamed = Vec::new();
+        renderer.render_to(&mut streamed).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), rendered);
+    }
+
+    #[test]
+    fn reduce_colors() {
+        let f = MyFormat::read_local("examples/hello.toml").unwrap();
+        let board = f.parse_rc::<BinaryBlock>();
+        assert_eq!(board.write().reduce_colors(), 0);
+
+        let f = MyFormat::read_local("examples/UK.toml").unwrap();
+        let board = f.parse_rc::<ColoredBlock>();
+        assert!(board.write().reduce_colors() > 0);
+        // nothing more to reduce
+        assert_eq!(board.write().reduce_colors(), 0);
+    }
+
+    #[test]
+    fn binary_without_legend() {
+        let f = MyFormat::read_local("examples/hello.toml").unwrap();
+        let board = f.parse_rc::<BinaryBlock>();
+
+        let renderer = ShellRenderer::with_board(board.clone());
+        let legend_renderer = ShellRenderer::with_board(board).with_legend(true);
+        assert_eq!(renderer.render(), legend_renderer.render());
     }
 }
 
@@ -134,7 +352,7 @@ mod web {
         assert_eq!(nop.infer_scheme(), PuzzleScheme::BlackAndWhite);
         assert_eq!(nop.encoded().len(), 40);
 
-        let (colors, solution) = nop.decipher();
+        let (colors, solution) = nop.decipher().unwrap();
         assert_eq!(colors, ["000000"]);
         assert_eq!(
             solution,
@@ -156,7 +374,7 @@ mod web {
         assert_eq!(nop.infer_scheme(), PuzzleScheme::MultiColor);
         assert_eq!(nop.encoded().len(), 45);
 
-        let (colors, solution) = nop.decipher();
+        let (colors, solution) = nop.decipher().unwrap();
         assert_eq!(colors, ["fbf204", "000000", "f4951c"]);
         assert_eq!(
             solution,
@@ -367,3 +585,818 @@ mod detect_webpbn_formats {
         assert_eq!(content.len(), 20 + 20 + 2);
     }
 }
+
+#[cfg(not(feature = "sat"))]
+mod backtracking {
+    use nonogrid::{
+        BacktrackingSolver, BinaryBlock, BinaryColor, Board, FullProbe, LineSolver, Point,
+        ProbeSolver, RcBoard, SearchCounters,
+    };
+
+    use super::{ambiguous_board, make_board};
+
+    type Solver = BacktrackingSolver<BinaryBlock, FullProbe<BinaryBlock>, LineSolver<BinaryBlock>>;
+
+    /// The pseudo-random 8x8 puzzle with 3 solutions
+    fn pseudo_random_board() -> RcBoard<BinaryBlock> {
+        let solution: Vec<Vec<u32>> = (0..8)
+            .map(|y| {
+                (0..8)
+                    .map(|x| u32::from((x * 7 + y * 3 + x * y) % 5 < 2))
+                    .collect()
+            })
+            .collect();
+        RcBoard::new(make_board(&solution))
+    }
+
+    fn first_solution(initial: Vec<(Point, BinaryColor)>) -> Vec<BinaryColor> {
+        let mut solver = Solver::with_options(ambiguous_board(), Some(1), None, None);
+        solver.set_initial_directions(initial);
+        solver.run().unwrap();
+        assert_eq!(solver.solutions.len(), 1);
+        solver.solutions.pop().unwrap()
+    }
+
+    #[test]
+    fn initial_directions() {
+        let b = BinaryColor::Black;
+        let w = BinaryColor::White;
+
+        let solution = first_solution(vec![(Point::new(1, 0), b)]);
+        assert_eq!(solution, vec![w, b, b, w]);
+
+        let solution = first_solution(vec![(Point::new(0, 0), b)]);
+        assert_eq!(solution, vec![b, w, w, b]);
+    }
+
+    #[test]
+    fn invalid_initial_directions_ignored() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        solver.set_initial_directions(vec![
+            (Point::new(5, 5), BinaryColor::Black),
+            (Point::new(0, 0), BinaryColor::Undefined),
+        ]);
+        solver.run().unwrap();
+        assert_eq!(solver.solutions.len(), 2);
+    }
+
+    #[test]
+    fn clear_cache_on_restore() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        solver.set_max_cache_size(0);
+        solver.run().unwrap();
+        assert_eq!(solver.solutions.len(), 2);
+    }
+
+    #[test]
+    fn required_guessing() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        solver.run().unwrap();
+        assert!(solver.required_guessing());
+
+        // the whole black square is solved without any guess
+        let board = RcBoard::new(make_board(&[vec![1, 1], vec![1, 1]]));
+        let mut solver = Solver::with_options(board.clone(), None, None, None);
+        solver.run().unwrap();
+        assert!(board.read().is_solved_full());
+        assert!(!solver.required_guessing());
+    }
+
+    #[test]
+    fn solution_path() {
+        let b = BinaryColor::Black;
+
+        let mut solver = Solver::with_options(ambiguous_board(), Some(1), None, None);
+        solver.set_initial_directions(vec![(Point::new(1, 0), b)]);
+        solver.run().unwrap();
+        assert_eq!(solver.solution_path(), &[(Point::new(1, 0), b)]);
+
+        // the whole black square is solved without any guess
+        let board = RcBoard::new(make_board(&[vec![1, 1], vec![1, 1]]));
+        let mut solver = Solver::with_options(board, None, None, None);
+        solver.run().unwrap();
+        assert!(solver.solution_path().is_empty());
+    }
+
+    #[test]
+    fn stats() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        assert!(solver.stats().is_none());
+        solver.run().unwrap();
+
+        let stats = solver.stats();
+        if cfg!(feature = "std_time") {
+            let stats = stats.unwrap();
+            assert_eq!(stats.solved_cells, 4);
+            assert!(stats.cells_per_second() > 0.0);
+        } else {
+            assert!(stats.is_none());
+        }
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let solutions = || {
+            let mut solver = Solver::with_options(pseudo_random_board(), None, None, None);
+            solver.run().unwrap();
+            solver.solutions
+        };
+
+        let first = solutions();
+        assert_eq!(first.len(), 3);
+        for _ in 0..5 {
+            assert_eq!(solutions(), first);
+        }
+    }
+
+    #[test]
+    fn choose_strategies() {
+        use nonogrid::ChoosePixel;
+
+        for &strategy in &[
+            ChoosePixel::Sum,
+            ChoosePixel::Min,
+            ChoosePixel::Max,
+            ChoosePixel::Mul,
+            ChoosePixel::Sqrt,
+            ChoosePixel::MinLogm,
+            ChoosePixel::MinLogd,
+        ] {
+            let mut solver =
+                Solver::with_options(ambiguous_board(), None, None, None).with_strategy(strategy);
+            solver.run().unwrap();
+            assert_eq!(solver.solutions.len(), 2, "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn search_counters() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        assert_eq!(solver.search_counters(), SearchCounters::default());
+        solver.run().unwrap();
+
+        let stats = solver.search_counters();
+        assert_eq!(stats.probes, 4);
+        assert_eq!(stats.nodes_visited, 1);
+        assert_eq!(stats.depth_reached, solver.depth_reached());
+    }
+
+    #[test]
+    fn search_counters_regression() {
+        let mut solver = Solver::with_options(pseudo_random_board(), None, None, None);
+        solver.run().unwrap();
+        assert_eq!(solver.solutions.len(), 3);
+
+        let stats = solver.search_counters();
+        assert!(stats.nodes_visited <= 30, "{:?}", stats);
+        assert!(stats.probes <= 500, "{:?}", stats);
+        assert!(stats.contradictions > 0);
+        assert!(stats.cache_hits > stats.cache_misses);
+    }
+
+    #[test]
+    fn difficulty() {
+        use nonogrid::{
+            classify_difficulty, classify_difficulty_with, DifficultyThresholds, DifficultyTier,
+        };
+
+        let board = ambiguous_board();
+        let board = board.read();
+        let tier = classify_difficulty::<_, LineSolver<_>, FullProbe<_>>(&board).unwrap();
+        assert_eq!(tier, DifficultyTier::Medium);
+
+        let thresholds = DifficultyThresholds {
+            max_medium_guesses: 0,
+            ..DifficultyThresholds::default()
+        };
+        let tier = classify_difficulty_with::<_, LineSolver<_>, FullProbe<_>>(&board, &thresholds)
+            .unwrap();
+        assert_eq!(tier, DifficultyTier::Hard);
+
+        let thresholds = DifficultyThresholds {
+            max_medium_depth: 0,
+            max_hard_depth: 0,
+            ..DifficultyThresholds::default()
+        };
+        let tier = classify_difficulty_with::<_, LineSolver<_>, FullProbe<_>>(&board, &thresholds)
+            .unwrap();
+        assert_eq!(tier, DifficultyTier::Extreme);
+    }
+
+    #[test]
+    fn probe_impact() {
+        use nonogrid::ProbeImpact;
+
+        let board = ambiguous_board();
+        let mut probe_solver = FullProbe::with_board(board.clone());
+        let impact = probe_solver.run_unsolved::<LineSolver<_>>().unwrap();
+        // every probe of the 2x2 diagonal solves the whole board
+        assert_eq!(impact.len(), 8);
+        let all_probes: Vec<ProbeImpact<_>> = impact.clone();
+        for probe in &all_probes {
+            assert_eq!(probe.cells_solved().len(), 3);
+            assert!(probe
+                .cells_solved()
+                .iter()
+                .all(|(point, _color)| *point != probe.point()));
+            assert_ne!(probe.color(), BinaryColor::Undefined);
+        }
+
+        let (point, color, cells_solved, _priority) = impact[0].clone().into_tuple();
+        assert_eq!(point, all_probes[0].point());
+        assert_eq!(color, all_probes[0].color());
+        assert_eq!(cells_solved, all_probes[0].cells_solved());
+    }
+
+    #[test]
+    fn run_with_impact() {
+        let board = ambiguous_board();
+        let mut probe_solver = FullProbe::with_board(board.clone());
+        let impact = probe_solver.run_unsolved::<LineSolver<_>>().unwrap();
+
+        let mut solver = Solver::with_options(board, None, None, None);
+        solver.run_with_impact(impact).unwrap();
+        assert_eq!(solver.solutions.len(), 2);
+        assert!(solver.required_guessing());
+    }
+
+    #[test]
+    fn cell_priorities() {
+        let board = ambiguous_board();
+        let probe_solver = FullProbe::with_board(board.clone());
+        let priorities = probe_solver.cell_priorities();
+        assert_eq!(priorities.len(), 4);
+        // all the cells are equal: two unsolved neighbours and no solved cells in the lines
+        assert!(priorities.iter().all(|(_point, priority)| *priority == 2.0));
+
+        Board::set_color_with_callback(&board, &Point::new(0, 0), &BinaryColor::Black);
+        let priorities = probe_solver.cell_priorities();
+        assert_eq!(
+            priorities,
+            vec![
+                (Point::new(1, 0), 3.5),
+                (Point::new(0, 1), 3.5),
+                (Point::new(1, 1), 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn probe_cell() {
+        let b = BinaryColor::Black;
+        let w = BinaryColor::White;
+
+        let board = ambiguous_board();
+        let mut probe_solver = FullProbe::with_board(board.clone());
+        let mut solved = probe_solver
+            .probe_cell::<LineSolver<_>>(Point::new(0, 0), b)
+            .unwrap();
+        solved.sort_by_key(|(point, _color)| *point);
+        assert_eq!(
+            solved,
+            vec![
+                (Point::new(0, 1), w),
+                (Point::new(1, 0), w),
+                (Point::new(1, 1), b)
+            ]
+        );
+        assert!(!board.read().differs(&[BinaryColor::Undefined; 4]));
+
+        assert!(probe_solver
+            .probe_cell::<LineSolver<_>>(Point::new(0, 0), BinaryColor::Undefined)
+            .is_err());
+
+        // the second column is empty
+        let board = RcBoard::new(make_board(&[vec![1, 0], vec![1, 0]]));
+        let mut probe_solver = FullProbe::with_board(board);
+        assert!(probe_solver
+            .probe_cell::<LineSolver<_>>(Point::new(1, 0), b)
+            .is_err());
+    }
+
+    #[test]
+    fn ambiguous_cells() {
+        use nonogrid::render::{Renderer, ShellRenderer};
+
+        let board = ambiguous_board();
+        let mut solver = Solver::with_options(board.clone(), None, None, None);
+        solver.run().unwrap();
+        let solutions = &solver.solutions;
+        assert_eq!(solutions.len(), 2);
+
+        let all_cells = vec![
+            Point::new(0, 0),
+            Point::new(1, 0),
+            Point::new(0, 1),
+            Point::new(1, 1),
+        ];
+        assert_eq!(board.read().ambiguous_cells(solutions), all_cells);
+        assert!(board.read().ambiguous_cells(&solutions[..1]).is_empty());
+        assert!(board.read().ambiguous_cells(&[]).is_empty());
+
+        let before = board.read().make_snapshot();
+        board
+            .write()
+            .set_callback_on_restore(|| panic!("The rendering should not restore the board"));
+        let renderer = ShellRenderer::with_board(board.clone());
+        let mut rendered = renderer.render_solutions(solutions);
+        rendered.sort();
+        assert_eq!(
+            rendered,
+            vec![".\u{25A0}\n\u{25A0}.", "\u{25A0}.\n.\u{25A0}"]
+        );
+        assert!(!board.read().differs(&before));
+    }
+
+    #[test]
+    #[cfg(feature = "std_time")]
+    fn partial_solution_on_timeout() {
+        let mut solver = Solver::with_options(ambiguous_board(), None, None, None);
+        solver.run().unwrap();
+        assert!(solver.partial_solution.is_none());
+
+        let board = ambiguous_board();
+        let mut solver = Solver::with_options(board.clone(), None, Some(0), None);
+        solver.run().unwrap();
+        assert!(solver.solutions.is_empty());
+        assert_eq!(
+            solver.partial_solution,
+            Some(vec![BinaryColor::Undefined; 4])
+        );
+        assert!(!board.read().is_solved_full());
+        assert!(!solver.timed_out_in_propagation());
+    }
+
+    #[test]
+    #[cfg(feature = "std_time")]
+    fn no_partial_solution_if_not_interrupted() {
+        // the search stops on the solutions limit before checking the (already passed) deadline
+        let mut solver = Solver::with_options(ambiguous_board(), Some(0), Some(0), None);
+        solver.run().unwrap();
+        assert!(solver.solutions.is_empty());
+        assert!(solver.partial_solution.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std_time")]
+    fn timeout_in_propagation() {
+        let board = ambiguous_board();
+        let solver =
+            nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), None, Some(0), None)
+                .unwrap()
+                .unwrap();
+
+        assert!(solver.timed_out_in_propagation());
+        assert!(solver.solutions.is_empty());
+        assert_eq!(
+            solver.partial_solution,
+            Some(vec![BinaryColor::Undefined; 4])
+        );
+        assert!(!board.read().is_solved_full());
+    }
+}
+
+mod trivial {
+    use nonogrid::{
+        clues_from_solution, BinaryBlock, BinaryColor, Board, FullProbe, LineSolver, RcBoard,
+    };
+
+    fn make_board(solution: &[Vec<u32>]) -> RcBoard<BinaryBlock> {
+        RcBoard::new(super::make_board(solution))
+    }
+
+    #[cfg(not(feature = "sat"))]
+    fn solve(board: &RcBoard<BinaryBlock>) -> Result<bool, String> {
+        nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), None, None, None)
+            .map(|backtracking| backtracking.is_none())
+    }
+
+    #[cfg(feature = "sat")]
+    fn solve(board: &RcBoard<BinaryBlock>) -> Result<bool, String> {
+        nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board.clone(), None)
+            .map(|solutions| solutions.is_none())
+    }
+
+    #[test]
+    fn all_empty() {
+        let board = make_board(&vec![vec![0; 7]; 5]);
+        assert!(solve(&board).unwrap());
+
+        let board = board.read();
+        assert!(board.is_solved_full());
+        assert!(board
+            .iter_rows()
+            .all(|row| row.iter().all(|&cell| cell == BinaryColor::White)));
+    }
+
+    #[test]
+    fn all_full() {
+        let board = make_board(&vec![vec![1; 7]; 5]);
+        assert!(solve(&board).unwrap());
+
+        let board = board.read();
+        assert!(board.is_solved_full());
+        assert!(board
+            .iter_rows()
+            .all(|row| row.iter().all(|&cell| cell == BinaryColor::Black)));
+    }
+
+    #[test]
+    fn solved_by_propagation() {
+        // the rows are not determined by their clues alone
+        let board = make_board(&[vec![1, 0, 0], vec![1, 1, 0], vec![1, 1, 1]]);
+        assert!(solve(&board).unwrap());
+        assert!(board.read().is_solved_full());
+    }
+
+    #[test]
+    fn contradicting_columns() {
+        let (columns, _rows) = clues_from_solution::<BinaryBlock>(&vec![vec![1; 3]; 3], 0);
+        let (_columns, rows) = clues_from_solution(&vec![vec![0; 3]; 3], 0);
+        let board = RcBoard::new(Board::with_descriptions_and_palette(rows, columns, None));
+        assert!(solve(&board).is_err());
+    }
+}
+
+mod from_point {
+    use nonogrid::{solve_from_point, BinaryColor, Board, LineSolver, Point};
+
+    use super::ambiguous_board;
+
+    #[test]
+    fn consequences_of_the_move() {
+        let board = ambiguous_board();
+        let point = Point::new(0, 0);
+        Board::set_color_with_callback(&board, &point, &BinaryColor::Black);
+
+        let mut solved = solve_from_point::<_, LineSolver<_>>(board.clone(), point).unwrap();
+        solved.sort();
+        assert_eq!(
+            solved,
+            vec![Point::new(0, 1), Point::new(1, 0), Point::new(1, 1)]
+        );
+        assert!(board.read().is_solved_full());
+    }
+
+    #[test]
+    fn contradicting_move() {
+        let board = ambiguous_board();
+        Board::set_color_with_callback(&board, &Point::new(0, 0), &BinaryColor::Black);
+        Board::set_color_with_callback(&board, &Point::new(1, 0), &BinaryColor::Black);
+
+        assert!(solve_from_point::<_, LineSolver<_>>(board, Point::new(1, 0)).is_err());
+    }
+}
+
+mod borrowed {
+    use nonogrid::{
+        has_unique_solution, BinaryBlock, BinaryColor, Board, FullProbe, LineSolver, RcBoard,
+    };
+
+    use super::make_board;
+
+    #[cfg(not(feature = "sat"))]
+    fn solve(board: &mut Board<BinaryBlock>) -> Vec<Vec<BinaryColor>> {
+        nonogrid::solve_ref::<_, LineSolver<_>, FullProbe<_>>(board, None, None, None).unwrap()
+    }
+
+    #[cfg(feature = "sat")]
+    fn solve(board: &mut Board<BinaryBlock>) -> Vec<Vec<BinaryColor>> {
+        nonogrid::solve_ref::<_, LineSolver<_>, FullProbe<_>>(board, None).unwrap()
+    }
+
+    #[test]
+    fn unique() {
+        let mut board = make_board(&[vec![1, 1, 1], vec![1, 0, 0], vec![1, 1, 0]]);
+        let solutions = solve(&mut board);
+
+        assert!(board.is_solved_full());
+        assert_eq!(solutions, vec![board.make_snapshot()]);
+    }
+
+    #[test]
+    fn ambiguous() {
+        let mut board = make_board(&[vec![1, 0], vec![0, 1]]);
+        let mut solutions = solve(&mut board);
+        solutions.sort_unstable_by_key(|solution| solution[0] == BinaryColor::White);
+
+        assert!(!board.is_solved_full());
+        let (b, w) = (BinaryColor::Black, BinaryColor::White);
+        assert_eq!(solutions, vec![vec![b, w, w, b], vec![w, b, b, w]]);
+    }
+
+    fn is_unique(solution: &[Vec<u32>]) -> bool {
+        let board = RcBoard::new(make_board(solution));
+        let unique = has_unique_solution::<_, LineSolver<_>, FullProbe<_>>(board.clone()).unwrap();
+        // the board is not changed
+        assert_eq!(board.read().solution_rate(), 0.0);
+        unique
+    }
+
+    #[test]
+    fn uniqueness() {
+        assert!(is_unique(&[vec![1, 1, 1], vec![1, 0, 0], vec![1, 1, 0]]));
+        assert!(!is_unique(&[vec![1, 0], vec![0, 1]]));
+    }
+}
+
+mod batch_change {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use nonogrid::{
+        BinaryBlock, FullProbe, LineSolver, Point, ProbeSolver, PropagationSolver, RcBoard,
+    };
+
+    use super::{ambiguous_board, make_board};
+
+    fn record_batches(board: &RcBoard<BinaryBlock>) -> Arc<Mutex<Vec<Vec<Point>>>> {
+        let batches = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&batches);
+        board.write().set_callback_on_batch_change(move |points| {
+            recorded.lock().unwrap().push(points.to_vec());
+        });
+        batches
+    }
+
+    #[test]
+    fn propagation() {
+        let board = RcBoard::new(make_board(&[vec![1, 1, 1], vec![1, 0, 0], vec![1, 0, 1]]));
+        let batches = record_batches(&board);
+        let before = board.read().make_snapshot();
+
+        let mut solver = PropagationSolver::new(board.clone());
+        let solved = solver.run::<LineSolver<_>>(None).unwrap();
+
+        let batches = batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        let reported: HashSet<_> = batches.iter().flatten().copied().collect();
+        assert_eq!(reported, solved.into_iter().collect());
+
+        let width = board.read().width();
+        let after = board.read().make_snapshot();
+        let changed: HashSet<_> = before
+            .iter()
+            .zip(&after)
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(index, _)| Point::new(index % width, index / width))
+            .collect();
+        assert_eq!(reported, changed);
+    }
+
+    #[test]
+    fn probes_are_not_reported() {
+        // every probe solves the whole board, but nothing is solved for sure
+        let board = ambiguous_board();
+        let batches = record_batches(&board);
+
+        let mut solver = FullProbe::with_board(board.clone());
+        let impact = solver.run_unsolved::<LineSolver<_>>().unwrap();
+        assert!(!impact.is_empty());
+        assert!(!board.read().is_solved_full());
+        assert!(batches.lock().unwrap().is_empty());
+    }
+}
+
+mod spoj {
+    use nonogrid::solve_spoj_stream;
+
+    fn solve(input: &str) -> String {
+        let mut output = vec![];
+        solve_spoj_stream(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn small_puzzles() {
+        let input = "2\n2 3\n1 1 0\n2 0\n1 0\n1 0\n2 0\n1 1\n0\n0\n";
+        assert_eq!(solve(input), "#.#\n.##\n.\n");
+    }
+
+    #[test]
+    fn unexpected_end() {
+        let mut output = vec![];
+        assert!(solve_spoj_stream("1\n2 2\n1 0\n".as_bytes(), &mut output).is_err());
+    }
+
+    #[test]
+    fn unsolvable_puzzle_skipped() {
+        // the first puzzle requires the black cell in the row, but not in the column
+        let input = "2\n1 1\n1 0\n0\n1 1\n1 0\n1 0\n";
+        assert_eq!(solve(input), "#\n");
+    }
+
+    #[test]
+    fn oversized_puzzle() {
+        let size = 1 << 12;
+        let input = format!("1\n{} {}\n{}", size + 1, size, "0\n".repeat(2 * size + 1));
+
+        let mut output = vec![];
+        assert!(solve_spoj_stream(input.as_bytes(), &mut output).is_err());
+    }
+}
+
+mod render {
+    use nonogrid::{
+        render::{Renderer, ShellRenderer},
+        Block, Board, Color, ColoredBlock, Description, RcBoard,
+    };
+
+    type Cell = <ColoredBlock as Block>::Color;
+
+    /// The colored board without the palette:
+    /// the first cell is still unknown, the second one is surely blank.
+    fn partial_board() -> RcBoard<ColoredBlock> {
+        let red = 4;
+        let rows = vec![Description::new(vec![ColoredBlock::from_size_and_color(
+            1, red,
+        )])];
+        let columns = vec![
+            Description::new(vec![ColoredBlock::from_size_and_color(1, red)]),
+            Description::new(vec![]),
+        ];
+        let board = RcBoard::new(Board::with_descriptions_and_palette(rows, columns, None));
+        Board::restore_with_callback(&board, vec![Cell::from_color_ids(&[1, 4]), Cell::blank()]);
+        board
+    }
+
+    #[test]
+    fn blank_differs_from_unknown() {
+        let board = partial_board();
+        assert_eq!(board.read().to_string(), "?.");
+
+        let renderer = ShellRenderer::with_board(board.clone());
+        assert_eq!(renderer.render_grid(), "?.");
+        assert_eq!(renderer.render_simple(), "?.");
+
+        let renderer = ShellRenderer::with_board(board).with_unknown_symbol('_');
+        assert_eq!(renderer.render_simple(), "_.");
+    }
+
+    #[test]
+    fn html() {
+        use nonogrid::render::HtmlRenderer;
+
+        let renderer = HtmlRenderer::with_board(partial_board());
+        assert_eq!(
+            renderer.render_simple(),
+            "<table>\n\
+             <tr><td class=\"unsolved\"></td><td style=\"background-color: #ffffff\"></td></tr>\n\
+             </table>"
+        );
+
+        let rendered = renderer.render();
+        assert!(rendered.starts_with("<style>td.unsolved {"));
+        assert!(rendered.contains("<tr><th></th><th>1</th><th></th></tr>"));
+        assert!(rendered.contains("<tr><th>1</th><td class=\"unsolved\"></td>"));
+    }
+
+    #[test]
+    fn braille() {
+        use nonogrid::{render::BrailleRenderer, BinaryColor};
+
+        let solution = vec![
+            vec![1, 0, 1],
+            vec![0, 1, 0],
+            vec![1, 1, 1],
+            vec![0, 0, 0],
+            vec![1, 0, 0],
+        ];
+        let board = RcBoard::new(super::make_board(&solution));
+        let cells = solution
+            .iter()
+            .flatten()
+            .map(|&cell| {
+                if cell == 1 {
+                    BinaryColor::Black
+                } else {
+                    BinaryColor::White
+                }
+            })
+            .collect();
+        Board::restore_with_callback(&board, cells);
+
+        let renderer = BrailleRenderer::with_board(board);
+        assert_eq!(
+            renderer.render_simple(),
+            "\u{2835}\u{2805}\n\u{2801}\u{2800}"
+        );
+        assert_eq!(renderer.render(), renderer.render_simple());
+    }
+
+    #[test]
+    fn render_to() {
+        use nonogrid::render::HtmlRenderer;
+
+        let board = partial_board();
+        let mut streamed = Vec::new();
+        ShellRenderer::with_board(board.clone())
+            .render_to(&mut streamed)
+            .unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), "# 1   \n1 ? . ");
+
+        let renderer = HtmlRenderer::with_board(board);
+        let mut streamed = Vec::new();
+        renderer.render_to(&mut streamed).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), renderer.render());
+    }
+
+    #[test]
+    #[cfg(feature = "ini")]
+    fn html_colored() {
+        use nonogrid::{
+            parser::MyFormat, render::HtmlRenderer, BoardParser, LineSolver, PropagationSolver,
+        };
+
+        let f = MyFormat::with_content(
+            r#"
+            [clues]
+            rows = "1r 1"
+            columns = "1r, 1"
+
+            [colors]
+            defs = ["r = (red) %"]
+            "#,
+        )
+        .unwrap();
+        let board = f.parse_rc::<ColoredBlock>();
+        PropagationSolver::new(board.clone())
+            .run::<LineSolver<_>>(None)
+            .unwrap();
+        assert!(board.read().is_solved_full());
+
+        let rendered = HtmlRenderer::with_board(board).render();
+        assert!(rendered.contains("<th><span style=\"color: #ff0000\">1</span> 1</th>"));
+        assert!(rendered.contains(
+            "<td style=\"background-color: #ff0000\"></td>\
+             <td style=\"background-color: #000000\"></td>"
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn png_grid() {
+        use nonogrid::render::PngRenderer;
+
+        let black = [0, 0, 0];
+        let white = [255, 255, 255];
+        let unknown = [128, 128, 128];
+
+        let image = PngRenderer::with_board(partial_board()).render_image(3);
+        assert_eq!(image.dimensions(), (6, 3));
+        assert_eq!(image.get_pixel(0, 0).0, unknown);
+        assert_eq!(image.get_pixel(3, 0).0, white);
+
+        let image = PngRenderer::with_board(partial_board())
+            .with_grid(true)
+            .render_image(3);
+        // the 2px border, 1px between the cells
+        assert_eq!(image.dimensions(), (2 + 3 + 1 + 3 + 2, 2 + 3 + 2));
+        assert_eq!(image.get_pixel(1, 1).0, black);
+        assert_eq!(image.get_pixel(2, 2).0, unknown);
+        assert_eq!(image.get_pixel(5, 2).0, black);
+        assert_eq!(image.get_pixel(6, 2).0, white);
+        assert_eq!(image.get_pixel(9, 2).0, black);
+    }
+}
+
+#[cfg(feature = "sat")]
+mod sat {
+    use nonogrid::{BinaryBlock, FullProbe, LineSolver, RcBoard, DEFAULT_MAX_SAT_SOLUTIONS};
+
+    use super::make_board;
+
+    /// Every row and column has a single cell, so every permutation matrix is a solution
+    fn permutations_board(size: usize) -> RcBoard<BinaryBlock> {
+        let diagonal: Vec<Vec<u32>> = (0..size)
+            .map(|i| (0..size).map(|j| if i == j { 1 } else { 0 }).collect())
+            .collect();
+        RcBoard::new(make_board(&diagonal))
+    }
+
+    fn solutions_count(board: RcBoard<BinaryBlock>, max_solutions: Option<usize>) -> usize {
+        nonogrid::solve::<_, LineSolver<_>, FullProbe<_>>(board, max_solutions)
+            .unwrap()
+            .unwrap()
+            .count()
+    }
+
+    #[test]
+    fn max_solutions() {
+        assert_eq!(solutions_count(permutations_board(4), Some(5)), 5);
+        // 4! permutations
+        assert_eq!(solutions_count(permutations_board(4), Some(usize::MAX)), 24);
+    }
+
+    #[test]
+    fn default_max_solutions() {
+        // 7! = 5040 permutations
+        assert_eq!(
+            solutions_count(permutations_board(7), None),
+            DEFAULT_MAX_SAT_SOLUTIONS
+        );
+    }
+}